@@ -8,3 +8,8 @@ pub const LOGARITHMIC_BASE: f64 = E;
 pub const LINEAR_MULTIPLIER: f64 = 1.0;
 
 pub const WORD_LENGTH_PADDING: usize = 100;
+
+/// Wrong answers after which a word counts as a leech.
+pub const LEECH_LAPSES: u32 = 8;
+
+pub const MINE_SHORTCUT: &str = "CommandOrControl+Shift+M";
@@ -1,5 +1,7 @@
 use std::fmt::{Debug, Display};
 
+use serde::Serialize;
+
 use crate::tools::xml::DictParseError;
 
 #[derive(thiserror::Error)]
@@ -57,9 +59,47 @@ impl From<Error> for String {
     }
 }
 
-impl From<DictParseError> for Error {
-    fn from(value: DictParseError) -> Self {
-        let s = format!("{:?}", value);
-        Error(s)
+impl From<Box<[DictParseError]>> for Error {
+    fn from(value: Box<[DictParseError]>) -> Self {
+        let lines: Vec<String> = value.iter().map(|e| e.to_string()).collect();
+        Error(lines.join("\n"))
+    }
+}
+
+/// Error returned to the frontend by dictionary imports, so parse failures arrive as a list.
+#[derive(Debug, Serialize)]
+#[serde(untagged)]
+pub enum ImportError {
+    Message(String),
+    Parse(Box<[DictParseError]>),
+}
+
+impl From<Error> for ImportError {
+    fn from(value: Error) -> Self {
+        ImportError::Message(value.take_msg())
+    }
+}
+
+impl From<&'static str> for ImportError {
+    fn from(o: &'static str) -> Self {
+        ImportError::Message(o.to_string())
+    }
+}
+
+impl From<String> for ImportError {
+    fn from(value: String) -> Self {
+        ImportError::Message(value)
+    }
+}
+
+impl From<std::io::Error> for ImportError {
+    fn from(e: std::io::Error) -> Self {
+        ImportError::Message(e.to_string())
+    }
+}
+
+impl From<Box<[DictParseError]>> for ImportError {
+    fn from(value: Box<[DictParseError]>) -> Self {
+        ImportError::Parse(value)
     }
 }
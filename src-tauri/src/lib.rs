@@ -13,15 +13,23 @@ mod words;
 use std::{
     fs::{create_dir_all, File},
     io::Read,
-    path::PathBuf,
+    path::{Path, PathBuf},
     sync::{Mutex, MutexGuard},
 };
 
 use constants::APP_DATA_FOLDER;
-use error::Error;
-use program::{Application, DictID, UserID};
-use tauri::Manager;
-use words::for_frontend::Word;
+use error::{Error, ImportError};
+use program::{
+    Application, BrowseSort, Card, ClozeOptions, CurrentWord, DictID, DictInfo, DictPage,
+    DrillOptions, LinkedWord, ListedWord, MinedWord, MixedSessionOptions, PairSessionOptions,
+    SearchHit, SessionOptions, TrashEntry, UserID, WordOverride,
+};
+use tauri::{AppHandle, Manager};
+use tauri_plugin_clipboard_manager::ClipboardExt;
+use words::{
+    for_frontend::Word, Cloze, DictDiff, DictPreview, DictUID, Dictionary, DuplicateMode,
+    LemmaReport, ObscurityMode, SearchOptions, WordFilter, WordID,
+};
 
 static APP: Mutex<Option<Application>> = Mutex::new(None);
 
@@ -34,17 +42,15 @@ fn init_app(app: Application) {
 }
 
 #[tauri::command]
-fn import_dict(filename: String) -> Result<(), String> {
-    match _import_dict(filename) {
-        Ok(_) => Ok(()),
-        Err(e) => Err(e)?,
-    }
+fn import_dict(
+    filename: String,
+    mode: Option<ObscurityMode>,
+    duplicates: Option<DuplicateMode>,
+) -> Result<(), ImportError> {
+    _import_dict(filename, mode, duplicates.unwrap_or(DuplicateMode::KeepAll))
 }
 
-fn _import_dict(filename: String) -> Result<(), Error> {
-    let mut mtx = get_app();
-    let app = mtx.as_mut().unwrap();
-
+fn import_path(filename: String) -> Result<PathBuf, ImportError> {
     let mut path = PathBuf::new();
     path.push(filename);
 
@@ -60,26 +66,72 @@ fn _import_dict(filename: String) -> Result<(), Error> {
         return Err("Must be file!")?;
     }
 
+    Ok(path)
+}
+
+fn read_dict_file(
+    path: &Path,
+    mode: Option<ObscurityMode>,
+    duplicates: DuplicateMode,
+) -> Result<Dictionary, ImportError> {
+    let dict = match path.extension().and_then(|o| o.to_str()) {
+        Some("dct") => {
+            let mut file = File::open(path)?;
+            Dictionary::load_from(&mut file)?.0
+        }
+        Some("xml") => {
+            let file = File::open(path)?;
+            tools::xml::parse_xml_dictionary(file, mode, duplicates)?
+        }
+        Some(other) => return Err(format!("Invalid file type: {}!", other))?,
+        None => return Err("No extension")?,
+    };
+
+    Ok(dict)
+}
+
+/// Folder the media of a dictionary file are read from. Media of an xml dictionary are next to
+/// it, a `.dct` bundle has its own media folder.
+fn media_source(path: &Path) -> PathBuf {
+    match path.extension().and_then(|o| o.to_str()) {
+        Some("dct") => tools::media::media_dir(path),
+        _ => path.parent().map(|p| p.to_owned()).unwrap_or_default(),
+    }
+}
+
+fn _import_dict(
+    filename: String,
+    mode: Option<ObscurityMode>,
+    duplicates: DuplicateMode,
+) -> Result<(), ImportError> {
+    let mut mtx = get_app();
+    let app = mtx.as_mut().unwrap();
+
+    let path = import_path(filename)?;
+
+    // dictionaries are known by their uid, overwriting one would orphan all knowledge of it
+    let to = app.installed_path(&path);
+    if to.exists() {
+        Err("A dictionary with this file name is already installed, update it instead!")?;
+    }
+
     match path.extension() {
         Some(o) => match o.to_str() {
             Some("dct") => {
-                let mut to = app.get_dict_dir();
-                to.push(path.file_name().unwrap());
-                std::fs::copy(path, to)?;
+                std::fs::copy(&path, &to)?;
+
+                let media = tools::media::media_dir(&to);
+                tools::media::copy_dir(&media_source(&path), &media)?;
             }
             Some("xml") => {
                 let file = File::open(&path)?;
-                let dict =
-                    tools::xml::parse_xml_dictionary(file, words::ObscurityMode::Linear(1f64))?;
-
-                let mut dct_path = PathBuf::new();
-                dct_path.push(app.get_dict_dir());
-
-                let new_name = path.file_stem().unwrap().to_str().unwrap().to_owned() + ".dct";
-                dct_path.push(new_name);
+                let dict = tools::xml::parse_xml_dictionary(file, mode, duplicates)?;
 
-                let mut dct_file = File::create(dct_path)?;
+                let mut dct_file = File::create(&to)?;
                 dict.save_to(&mut dct_file)?;
+
+                let media = tools::media::media_dir(&to);
+                tools::media::copy_media(&dict, &media_source(&path), &media)?;
             }
             Some(other) => return Err(format!("Invalid file type: {}!", other))?,
             None => return Err("Nothing??")?
@@ -90,6 +142,250 @@ fn _import_dict(filename: String) -> Result<(), Error> {
     Ok(())
 }
 
+#[tauri::command]
+fn preview_dict(filename: String, mode: Option<ObscurityMode>) -> Result<DictPreview, ImportError> {
+    let mtx = get_app();
+    let app = mtx.as_ref().unwrap();
+
+    let path = import_path(filename)?;
+
+    // keep every homograph so the preview can list them
+    let (dict, empty_fields) = match path.extension().and_then(|o| o.to_str()) {
+        Some("xml") => {
+            let file = File::open(&path)?;
+            tools::xml::preview_xml_dictionary(file, mode, DuplicateMode::KeepAll)?
+        }
+        _ => (
+            read_dict_file(&path, mode, DuplicateMode::KeepAll)?,
+            Vec::new(),
+        ),
+    };
+
+    Ok(app.preview_dict(&dict, empty_fields, &path))
+}
+
+#[tauri::command]
+fn update_dict(
+    dict: DictID,
+    filename: String,
+    mode: Option<ObscurityMode>,
+    duplicates: Option<DuplicateMode>,
+) -> Result<DictDiff, ImportError> {
+    let mut mtx = get_app();
+    let app = mtx.as_mut().unwrap();
+
+    let path = import_path(filename)?;
+    let new = read_dict_file(&path, mode, duplicates.unwrap_or(DuplicateMode::KeepAll))?;
+
+    // the media are checked up front but only copied once the update went through
+    let uid = dict.get_uid();
+    let source = media_source(&path);
+    let media = tools::media::find_media(&new, &source)?;
+
+    let diff = app.update_dict(dict, new)?;
+    app.install_media(uid, &media, &source)?;
+
+    Ok(diff)
+}
+
+#[tauri::command]
+fn add_word(dict: DictID, word: Word) -> Result<WordID, String> {
+    let mut mtx = get_app();
+    let app = mtx.as_mut().unwrap();
+
+    Ok(app.add_word(dict, word.into())?)
+}
+
+#[tauri::command]
+fn edit_word(dict: DictID, id: WordID, word: Word) -> Result<(), String> {
+    let mut mtx = get_app();
+    let app = mtx.as_mut().unwrap();
+
+    Ok(app.edit_word(dict, id, word.into())?)
+}
+
+#[tauri::command]
+fn delete_word(dict: DictID, id: WordID) -> Result<(), String> {
+    let mut mtx = get_app();
+    let app = mtx.as_mut().unwrap();
+
+    Ok(app.delete_word(dict, id)?)
+}
+
+#[tauri::command]
+fn set_dict_title(dict: DictID, title: String) -> Result<DictID, String> {
+    let mut mtx = get_app();
+    let app = mtx.as_mut().unwrap();
+
+    Ok(app.set_dict_title(dict, title)?)
+}
+
+fn _mine_clipboard(handle: &AppHandle) -> Result<MinedWord, Error> {
+    let text = handle.clipboard().read_text().map_err(|e| e.to_string())?;
+
+    let mut mtx = get_app();
+    let app = mtx.as_mut().ok_or("App not loaded!")?;
+
+    app.mine_word(text)
+}
+
+/// Mines the clipboard whenever the shortcut is pressed. Another program may hold the shortcut
+/// already, the app works without it.
+#[cfg(not(mobile))]
+fn register_mine_shortcut(handle: &AppHandle) {
+    use tauri::Emitter;
+    use tauri_plugin_global_shortcut::{GlobalShortcutExt, ShortcutState};
+
+    let registered =
+        handle
+            .global_shortcut()
+            .on_shortcut(constants::MINE_SHORTCUT, |handle, _, event| {
+                if event.state != ShortcutState::Pressed {
+                    return;
+                }
+
+                let word = match _mine_clipboard(handle) {
+                    Ok(word) => word,
+                    Err(e) => return eprintln!("Mining failed: {}", e.msg()),
+                };
+                if let Err(e) = handle.emit("word-mined", word) {
+                    eprintln!("Mined word couldn't be sent: {}", e);
+                }
+            });
+
+    if let Err(e) = registered {
+        eprintln!("Shortcut {} unavailable: {}", constants::MINE_SHORTCUT, e);
+    }
+}
+
+#[tauri::command]
+fn mine_clipboard(handle: AppHandle) -> Result<MinedWord, String> {
+    Ok(_mine_clipboard(&handle)?)
+}
+
+#[tauri::command]
+fn analyze_corpus(dict: DictID, folder: String) -> Result<(), String> {
+    let mut mtx = get_app();
+    let app = mtx.as_mut().unwrap();
+
+    app.analyze_corpus(dict, folder)?;
+
+    Ok(())
+}
+
+#[tauri::command]
+fn import_lemmas(
+    dict: DictID,
+    filename: String,
+    replace: Option<bool>,
+) -> Result<LemmaReport, ImportError> {
+    let mut mtx = get_app();
+    let app = mtx.as_mut().unwrap();
+
+    let path = import_path(filename)?;
+
+    Ok(app.import_lemmas(dict, path, replace.unwrap_or(false))?)
+}
+
+#[tauri::command]
+fn delete_dict(dict: DictID) -> Result<TrashEntry, String> {
+    let mut mtx = get_app();
+    let app = mtx.as_mut().unwrap();
+
+    Ok(app.delete_dict(dict)?)
+}
+
+#[tauri::command]
+fn delete_user(user: UserID) -> Result<TrashEntry, String> {
+    let mut mtx = get_app();
+    let app = mtx.as_mut().unwrap();
+
+    Ok(app.delete_user(user)?)
+}
+
+#[tauri::command]
+fn rename_user(user: UserID, name: String) -> Result<UserID, String> {
+    let mut mtx = get_app();
+    let app = mtx.as_mut().unwrap();
+
+    Ok(app.rename_user(user, name)?)
+}
+
+#[tauri::command]
+fn get_trash() -> Result<Box<[TrashEntry]>, String> {
+    let mtx = get_app();
+    let app = mtx.as_ref().unwrap();
+
+    Ok(app.get_trash()?)
+}
+
+#[tauri::command]
+fn restore_from_trash(id: String) -> Result<(), String> {
+    let mut mtx = get_app();
+    let app = mtx.as_mut().unwrap();
+
+    Ok(app.restore_from_trash(id)?)
+}
+
+#[tauri::command]
+fn empty_trash() -> Result<(), String> {
+    let mtx = get_app();
+    let app = mtx.as_ref().unwrap();
+
+    Ok(app.empty_trash()?)
+}
+
+#[tauri::command]
+fn export_dict(dict: DictID, folder: String) -> Result<String, String> {
+    let mtx = get_app();
+    let app = mtx.as_ref().unwrap();
+
+    let path = app.export_dict(dict, folder)?;
+
+    Ok(path.to_string_lossy().into_owned())
+}
+
+/// Serves `media://localhost/<dict id>/<file name>` from the dictionary's media folder.
+fn serve_media(request: &tauri::http::Request<Vec<u8>>) -> tauri::http::Response<Vec<u8>> {
+    let not_found = || {
+        tauri::http::Response::builder()
+            .status(404)
+            .body(Vec::new())
+            .unwrap()
+    };
+
+    let mut parts = request.uri().path().trim_start_matches('/').splitn(2, '/');
+    let uid = parts.next().and_then(DictUID::parse);
+    let name = parts.next().and_then(tools::media::percent_decode);
+
+    let path = match (uid, name) {
+        (Some(uid), Some(name)) => {
+            let mtx = get_app();
+            match mtx.as_ref().and_then(|app| app.get_media_path(uid, &name)) {
+                Some(path) => path,
+                None => return not_found(),
+            }
+        }
+        _ => return not_found(),
+    };
+
+    match std::fs::read(&path) {
+        Ok(data) => tauri::http::Response::builder()
+            .header("Content-Type", tools::media::mime_type(&path))
+            .body(data)
+            .unwrap(),
+        Err(_) => not_found(),
+    }
+}
+
+#[tauri::command]
+fn get_current_dict() -> Option<DictID> {
+    let mtx = get_app();
+    let app = mtx.as_ref().unwrap();
+
+    app.get_current_dict()
+}
+
 #[tauri::command]
 fn reload_files() {
     let mut mtx = get_app();
@@ -99,13 +395,150 @@ fn reload_files() {
 }
 
 #[tauri::command]
-fn get_dict_list() -> Box<[DictID]> {
+fn get_dict_list() -> Box<[DictInfo]> {
     let mtx = get_app();
     let app = mtx.as_ref().unwrap();
 
     app.get_dict_list()
 }
 
+#[tauri::command]
+fn search(query: String, options: Option<SearchOptions>) -> Box<[SearchHit]> {
+    let mtx = get_app();
+    let app = mtx.as_ref().unwrap();
+
+    app.search(&query, &options.unwrap_or_default())
+}
+
+#[tauri::command]
+fn browse_dict(
+    dict: DictID,
+    sort: Option<BrowseSort>,
+    descending: Option<bool>,
+    page: usize,
+    page_size: usize,
+) -> Result<DictPage, String> {
+    let mtx = get_app();
+    let app = mtx.as_ref().unwrap();
+
+    Ok(app.browse_dict(
+        dict,
+        sort.unwrap_or_default(),
+        descending.unwrap_or(false),
+        page,
+        page_size,
+    )?)
+}
+
+#[tauri::command]
+fn set_word_suspended(dict: DictID, id: WordID, suspended: bool) -> Result<(), String> {
+    let mut mtx = get_app();
+    let app = mtx.as_mut().unwrap();
+
+    Ok(app.set_word_suspended(dict, id, suspended)?)
+}
+
+#[tauri::command]
+fn get_word_note(dict: DictID, id: WordID) -> Result<Option<String>, String> {
+    let mtx = get_app();
+    let app = mtx.as_ref().unwrap();
+
+    Ok(app.get_word_note(dict, id)?)
+}
+
+#[tauri::command]
+fn set_word_note(dict: DictID, id: WordID, note: String) -> Result<(), String> {
+    let mut mtx = get_app();
+    let app = mtx.as_mut().unwrap();
+
+    Ok(app.set_word_note(dict, id, note)?)
+}
+
+#[tauri::command]
+fn get_word_override(dict: DictID, id: WordID) -> Result<Option<WordOverride>, String> {
+    let mtx = get_app();
+    let app = mtx.as_ref().unwrap();
+
+    Ok(app.get_word_override(dict, id)?)
+}
+
+#[tauri::command]
+fn set_word_override(
+    dict: DictID,
+    id: WordID,
+    definition: Option<String>,
+    pronunciation: Option<String>,
+) -> Result<(), String> {
+    let mut mtx = get_app();
+    let app = mtx.as_mut().unwrap();
+
+    Ok(app.set_word_override(dict, id, definition, pronunciation)?)
+}
+
+#[tauri::command]
+fn get_word_lists() -> Result<Box<[String]>, String> {
+    let mtx = get_app();
+    let app = mtx.as_ref().unwrap();
+
+    Ok(app.get_word_lists()?)
+}
+
+#[tauri::command]
+fn get_word_list(name: String) -> Result<Box<[ListedWord]>, String> {
+    let mtx = get_app();
+    let app = mtx.as_ref().unwrap();
+
+    Ok(app.get_word_list(&name)?)
+}
+
+#[tauri::command]
+fn get_lists_of_word(dict: DictID, id: WordID) -> Result<Box<[String]>, String> {
+    let mtx = get_app();
+    let app = mtx.as_ref().unwrap();
+
+    Ok(app.get_lists_of_word(dict, id)?)
+}
+
+#[tauri::command]
+fn create_word_list(name: String) -> Result<(), String> {
+    let mut mtx = get_app();
+    let app = mtx.as_mut().unwrap();
+
+    Ok(app.create_word_list(name)?)
+}
+
+#[tauri::command]
+fn delete_word_list(name: String) -> Result<(), String> {
+    let mut mtx = get_app();
+    let app = mtx.as_mut().unwrap();
+
+    Ok(app.delete_word_list(&name)?)
+}
+
+#[tauri::command]
+fn set_word_listed(name: String, dict: DictID, id: WordID, listed: bool) -> Result<(), String> {
+    let mut mtx = get_app();
+    let app = mtx.as_mut().unwrap();
+
+    Ok(app.set_word_listed(&name, dict, id, listed)?)
+}
+
+#[tauri::command]
+fn set_word_starred(dict: DictID, id: WordID, starred: bool) -> Result<(), String> {
+    let mut mtx = get_app();
+    let app = mtx.as_mut().unwrap();
+
+    Ok(app.set_word_starred(dict, id, starred)?)
+}
+
+#[tauri::command]
+fn star_current_word(starred: bool) -> Result<(), String> {
+    let mut mtx = get_app();
+    let app = mtx.as_mut().unwrap();
+
+    Ok(app.star_current_word(starred)?)
+}
+
 #[tauri::command]
 fn get_pool_size(dict: DictID) -> usize {
     let mtx = get_app();
@@ -131,13 +564,29 @@ fn pick_next_word() {
 }
 
 #[tauri::command]
-fn get_current_word() -> Option<Word> {
+fn get_current_word() -> Option<CurrentWord> {
     let mtx = get_app();
     let app = mtx.as_ref().unwrap();
 
     app.get_current_word()
 }
 
+#[tauri::command]
+fn get_current_card() -> Option<Card> {
+    let mtx = get_app();
+    let app = mtx.as_ref().unwrap();
+
+    app.get_current_card()
+}
+
+#[tauri::command]
+fn get_current_cloze() -> Option<Cloze> {
+    let mtx = get_app();
+    let app = mtx.as_ref().unwrap();
+
+    app.get_current_cloze()
+}
+
 #[tauri::command]
 fn get_users() -> Box<[UserID]> {
     let mtx = get_app();
@@ -164,6 +613,78 @@ fn start_practice_session() -> bool {
     true
 }
 
+#[tauri::command]
+fn start_custom_session(options: SessionOptions) -> bool {
+    let mut mtx = get_app();
+    let app = mtx.as_mut().unwrap();
+
+    app.start_custom_session(options)
+}
+
+#[tauri::command]
+fn start_drill_session(options: Option<DrillOptions>) -> bool {
+    let mut mtx = get_app();
+    let app = mtx.as_mut().unwrap();
+
+    app.start_drill_session(options.unwrap_or_default())
+}
+
+#[tauri::command]
+fn start_cloze_session(options: Option<ClozeOptions>) -> bool {
+    let mut mtx = get_app();
+    let app = mtx.as_mut().unwrap();
+
+    app.start_cloze_session(options.unwrap_or_default())
+}
+
+#[tauri::command]
+fn start_mixed_session(options: Option<MixedSessionOptions>) -> bool {
+    let mut mtx = get_app();
+    let app = mtx.as_mut().unwrap();
+
+    app.start_mixed_session(options.unwrap_or_default())
+}
+
+#[tauri::command]
+fn start_pair_session(options: PairSessionOptions) -> bool {
+    let mut mtx = get_app();
+    let app = mtx.as_mut().unwrap();
+
+    app.start_pair_session(options)
+}
+
+#[tauri::command]
+fn get_current_prompt() -> Option<Box<[LinkedWord]>> {
+    let mtx = get_app();
+    let app = mtx.as_ref().unwrap();
+
+    app.get_current_prompt()
+}
+
+#[tauri::command]
+fn get_linked_words(dict: DictID, id: WordID) -> Result<Box<[LinkedWord]>, String> {
+    let mtx = get_app();
+    let app = mtx.as_ref().unwrap();
+
+    Ok(app.get_linked_words(dict, id)?)
+}
+
+#[tauri::command]
+fn count_filtered_words(dict: DictID, filter: WordFilter) -> usize {
+    let mtx = get_app();
+    let app = mtx.as_ref().unwrap();
+
+    app.count_filtered_words(dict, &filter)
+}
+
+#[tauri::command]
+fn check_typed_answer(answer: String) -> bool {
+    let mtx = get_app();
+    let app = mtx.as_ref().unwrap();
+
+    app.check_typed_answer(&answer)
+}
+
 #[tauri::command]
 fn get_remaining_words() -> usize {
     let mtx = get_app();
@@ -239,18 +760,62 @@ pub fn run() {
     let builder = builder.plugin(tauri_plugin_global_shortcut::Builder::new().build());
 
     builder
+        .register_uri_scheme_protocol("media", |_, request| serve_media(&request))
         .invoke_handler(tauri::generate_handler![
             get_dict_list,
             set_dict,
+            get_current_dict,
             pick_next_word,
             get_current_word,
+            get_current_card,
+            get_current_cloze,
             get_users,
             create_user,
             reload_files,
             import_dict,
+            preview_dict,
+            update_dict,
+            add_word,
+            edit_word,
+            delete_word,
+            set_dict_title,
+            delete_dict,
+            delete_user,
+            rename_user,
+            get_trash,
+            restore_from_trash,
+            empty_trash,
+            mine_clipboard,
+            analyze_corpus,
+            import_lemmas,
+            export_dict,
+            search,
+            browse_dict,
+            set_word_suspended,
+            get_word_note,
+            set_word_note,
+            get_word_override,
+            set_word_override,
+            get_word_lists,
+            get_word_list,
+            get_lists_of_word,
+            create_word_list,
+            delete_word_list,
+            set_word_listed,
+            set_word_starred,
+            star_current_word,
             set_current_user,
             get_current_user,
             start_practice_session,
+            start_custom_session,
+            start_drill_session,
+            start_cloze_session,
+            start_mixed_session,
+            start_pair_session,
+            get_current_prompt,
+            check_typed_answer,
+            get_linked_words,
+            count_filtered_words,
             practice_current_word,
             get_remaining_words,
             conclude_session,
@@ -267,13 +832,19 @@ pub fn run() {
         
             let mut dict_path = base_path.clone();
             dict_path.push("dicts");
+
+            let mut trash_path = base_path.clone();
+            trash_path.push("trash");
         
             create_dir_all(&user_path).unwrap();
             create_dir_all(&dict_path).unwrap();
+            create_dir_all(&trash_path).unwrap();
 
             println!("{}", base_path.to_str().unwrap());
         
-            let mut appl = Application::new(user_path, dict_path, app.handle().to_owned()).unwrap();
+            let mut appl =
+                Application::new(user_path, dict_path, trash_path, app.handle().to_owned())
+                    .unwrap();
             appl.load(None).unwrap();
         
             let last_user_path = {
@@ -296,6 +867,9 @@ pub fn run() {
 
             init_app(appl);
 
+            #[cfg(not(mobile))]
+            register_mine_shortcut(app.handle());
+
             let main_window = app.get_webview_window("main").unwrap();
             #[cfg(not(mobile))]
             {
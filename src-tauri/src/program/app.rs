@@ -1,25 +1,34 @@
 use std::{
-    collections::HashMap,
-    fs::{metadata, read_dir, File},
+    cmp::Ordering,
+    collections::{HashMap, HashSet},
+    fs::{metadata, read_dir, remove_file, rename, File},
     io::Write,
     path::{Path, PathBuf},
     ptr,
     sync::Arc,
 };
 
-use chrono::{DateTime, Utc};
-use rand::Rng;
+use chrono::{DateTime, Duration, Local, TimeZone, Utc};
+use rand::{seq::SliceRandom, Rng};
 use serde::{Deserialize, Serialize};
 use tauri::{AppHandle, Manager};
 
 use crate::{
     constants::APP_DATA_FOLDER,
     error::Error,
-    tools::{dict_map::DictMap, weighted_list::pick_by_weight},
-    words::{Dictionary, FileVersion, Knowledge, WordID},
+    tools::{corpus, dict_map::DictMap, media, weighted_list::pick_by_weight},
+    words::{
+        Cloze, DictDiff, DictMetadata, DictPreview, DictUID, Dictionary, EmptyField, FileVersion,
+        Knowledge, LemmaReport, MatchKind, ObscurityMode, SearchField, SearchOptions, Word,
+        WordFilter, WordID, WordStatus, RECALL_THRESHOLD,
+    },
 };
 
-use super::{user::User, Progress};
+use super::{
+    trash::{move_into, Trash, TrashEntry, TrashKind},
+    user::{User, WordOverride, STARRED_LIST},
+    Progress,
+};
 
 macro_rules! to_dir_path {
     ($path: expr) => {{
@@ -32,17 +41,230 @@ macro_rules! to_dir_path {
     }};
 }
 
+/// Identifies a dictionary by its unique id. The name is only there for display.
 #[derive(Serialize, Deserialize, Clone)]
 pub struct DictID {
+    uid: DictUID,
     name: String,
 }
 
 impl DictID {
+    fn of(dict: &Dictionary) -> DictID {
+        DictID {
+            uid: dict.get_uid(),
+            name: dict.get_title().to_owned(),
+        }
+    }
+
+    pub fn get_uid(&self) -> DictUID {
+        self.uid
+    }
+
     pub fn get_name(&self) -> &str {
         self.name.as_str()
     }
 }
 
+/// A dictionary as listed for the user, with its descriptive metadata.
+#[derive(Serialize, Clone)]
+pub struct DictInfo {
+    #[serde(flatten)]
+    pub id: DictID,
+    pub word_count: usize,
+    pub metadata: DictMetadata,
+}
+
+/// A word captured into a user's personal dictionary, so the frontend can fill in the rest.
+#[derive(Serialize, Clone)]
+pub struct MinedWord {
+    pub dict: DictID,
+    pub id: WordID,
+    pub word: crate::words::for_frontend::Word,
+}
+
+/// A word found by a search, with how well the current user knows it.
+#[derive(Serialize, Clone)]
+pub struct SearchHit {
+    pub dict: DictID,
+    pub id: WordID,
+    pub word: crate::words::for_frontend::Word,
+    pub field: SearchField,
+    pub kind: MatchKind,
+    pub score: u32,
+    /// `None` if the user never practiced this dictionary
+    pub status: Option<WordStatus>,
+}
+
+#[derive(Deserialize, Clone, Copy, Default)]
+pub enum BrowseSort {
+    #[default]
+    Obscurity,
+    Alphabetical,
+    /// Soonest due first, words that were never practiced last
+    Due,
+    /// Least likely to be recalled first
+    Recall,
+}
+
+#[derive(Serialize, Clone)]
+pub struct BrowsedWord {
+    pub id: WordID,
+    pub word: crate::words::for_frontend::Word,
+    /// `None` if the user never practiced this dictionary
+    pub status: Option<WordStatus>,
+}
+
+/// One page of a dictionary's words.
+#[derive(Serialize, Clone)]
+pub struct DictPage {
+    pub page: usize,
+    pub page_count: usize,
+    pub total: usize,
+    pub words: Box<[BrowsedWord]>,
+}
+
+/// A practice session on words picked by a filter instead of by due date.
+#[derive(Deserialize, Clone)]
+#[serde(default)]
+pub struct SessionOptions {
+    pub filter: WordFilter,
+    /// Practice without changing the stored knowledge, e.g. right before a test
+    pub cram: bool,
+    /// Most words in the session, `None` for all matching words
+    pub limit: Option<usize>,
+    /// Only words of this word list of the user
+    pub list: Option<String>,
+}
+
+impl Default for SessionOptions {
+    fn default() -> Self {
+        SessionOptions {
+            filter: WordFilter::default(),
+            cram: false,
+            limit: Some(20),
+            list: None,
+        }
+    }
+}
+
+#[derive(Deserialize, Clone)]
+pub struct DictQuota {
+    pub dict: DictID,
+    /// Most words taken from this dictionary
+    pub quota: usize,
+}
+
+/// A review of the due words of several dictionaries at once.
+#[derive(Deserialize, Clone)]
+#[serde(default)]
+pub struct MixedSessionOptions {
+    /// Dictionaries to review, `None` for every dictionary the user has practiced
+    pub dicts: Option<Vec<DictQuota>>,
+    /// Quota of every dictionary when `dicts` is `None`
+    pub quota: usize,
+}
+
+impl Default for MixedSessionOptions {
+    fn default() -> Self {
+        MixedSessionOptions {
+            dicts: None,
+            quota: 10,
+        }
+    }
+}
+
+/// A session asking for the words of one dictionary given their linked words in another.
+#[derive(Deserialize, Clone)]
+pub struct PairSessionOptions {
+    /// Dictionary the shown words come from
+    pub prompt: DictID,
+    /// Dictionary the words to recall come from, its knowledge records the answers
+    pub answer: DictID,
+}
+
+/// A drill on the attributes of the words being learned, e.g. their genders or conjugations.
+#[derive(Deserialize, Clone)]
+#[serde(default)]
+pub struct DrillOptions {
+    /// Names of the attributes to drill, every attribute when empty
+    pub attributes: Vec<String>,
+    /// Most cards in the session
+    pub limit: usize,
+}
+
+impl Default for DrillOptions {
+    fn default() -> Self {
+        DrillOptions {
+            attributes: Vec::new(),
+            limit: 20,
+        }
+    }
+}
+
+/// A session of cloze cards made from the example sentences of the words being learned.
+#[derive(Deserialize, Clone)]
+#[serde(default)]
+pub struct ClozeOptions {
+    /// Most cards in the session
+    pub limit: usize,
+}
+
+impl Default for ClozeOptions {
+    fn default() -> Self {
+        ClozeOptions { limit: 20 }
+    }
+}
+
+/// What a practice card asks for.
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+pub enum Card {
+    /// The word, given its definition
+    Word,
+    /// The named attribute of the word, given the word
+    Attribute(String),
+    /// The word blanked out of the example with this index
+    Cloze(usize),
+}
+
+/// A word on one of the user's word lists.
+#[derive(Serialize, Clone)]
+pub struct ListedWord {
+    pub dict: DictID,
+    pub id: WordID,
+    pub word: crate::words::for_frontend::Word,
+}
+
+/// The word being practiced, with what the user added to it.
+#[derive(Serialize, Clone)]
+pub struct CurrentWord {
+    #[serde(flatten)]
+    pub word: crate::words::for_frontend::Word,
+    /// The user's own note on the word, e.g. a mnemonic
+    pub note: Option<String>,
+    /// The definition or pronunciation is the user's own
+    pub overridden: bool,
+    /// On the user's starred list
+    pub starred: bool,
+}
+
+/// A word linked to another through a shared concept.
+#[derive(Serialize, Clone)]
+pub struct LinkedWord {
+    pub dict: DictID,
+    pub id: WordID,
+    pub word: crate::words::for_frontend::Word,
+}
+
+impl LinkedWord {
+    fn of(dict: &Dictionary, id: WordID) -> LinkedWord {
+        LinkedWord {
+            dict: DictID::of(dict),
+            id,
+            word: dict.get_word_from_id(id).clone().into(),
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, Clone)]
 pub struct UserID {
     name: String,
@@ -54,108 +276,387 @@ impl UserID {
     }
 }
 
+/// Unix seconds of the next local midnight.
+fn next_midnight() -> i64 {
+    let now = Local::now();
+    let midnight = now
+        .naive_local()
+        .date()
+        .succ_opt()
+        .and_then(|d| d.and_hms_opt(0, 0, 0))
+        .and_then(|t| Local.from_local_datetime(&t).earliest());
+
+    match midnight {
+        Some(t) => t.timestamp(),
+        None => (now + Duration::days(1)).timestamp(),
+    }
+}
+
+/// Takes the knowledge of `dict` from `user`, or creates one if they haven't practiced it yet.
+fn take_knowledge_of(user: &mut User, dict: &Arc<Dictionary>) -> Knowledge {
+    let t = user
+        .get_knowledge()
+        .iter()
+        .find(|k| Arc::ptr_eq(&k.get_dict(), dict))
+        .map_or(ptr::null(), |k| k as *const Knowledge);
+
+    user.take_knowledge(t)
+        .unwrap_or_else(|| Knowledge::create(dict.clone()))
+}
+
+/// Picks up to `count` words of `dict` that are due, preferring the ones most likely forgotten.
+fn pick_due(
+    dict: &Dictionary,
+    knowledge: &Knowledge,
+    score_max: u32,
+    count: usize,
+    start_time: DateTime<Utc>,
+    keep: impl Fn(WordID) -> bool,
+) -> Vec<WordID> {
+    let potential_word_pool: Vec<(f32, WordID)> = dict
+        .get_words_leq_score(score_max)
+        .to_vec()
+        .into_iter()
+        .filter_map(|x| {
+            if knowledge.is_suspended(x) || knowledge.is_buried(x, start_time) || !keep(x) {
+                return None;
+            }
+
+            let k = knowledge.get_word_knowledge(x);
+            let pv = k.calculate_p_value(start_time);
+
+            if pv < RECALL_THRESHOLD {
+                Some((1.0 - pv, x))
+            } else {
+                None
+            }
+        })
+        .collect();
+
+    let mut word_pool;
+    if potential_word_pool.len() <= count {
+        word_pool = potential_word_pool.into_iter().map(|x| x.1).collect();
+    } else {
+        word_pool = Vec::new();
+
+        let mut pool = potential_word_pool.clone();
+
+        if pool.len() < 100 {
+            // If pool is small, only consider p value
+            for _ in 0..count {
+                let choice = {
+                    let i = pick_by_weight(&pool[..]);
+                    pool.remove(i)
+                };
+
+                word_pool.push(choice.1);
+            }
+        } else {
+            // If pool is big, also consider word obscurity
+            for _ in 0..count {
+                // pick 10, or all that are left once the quota has drained the pool below that
+                let candidates = pool.len().min(10);
+                let mut picked = Vec::new();
+                while picked.len() < candidates {
+                    let choice = pick_by_weight(&pool[..]);
+
+                    if !picked.contains(&choice) {
+                        picked.push(choice);
+                    }
+                }
+
+                // re-weight based on word obscurity
+                let max_obs = picked
+                    .iter()
+                    .map(|x| {
+                        let w = dict.get_word_from_id(pool[*x].1);
+
+                        w.obscurity
+                    })
+                    .max()
+                    .unwrap();
+                let picked: Vec<(f32, WordID)> = picked
+                    .iter()
+                    .map(|x| {
+                        let id = pool[*x].1;
+                        let w = dict.get_word_from_id(id);
+
+                        ((max_obs - w.obscurity + 1) as f32, id)
+                    })
+                    .collect();
+
+                let choice = pick_by_weight(&picked[..]);
+                word_pool.push(pool.remove(choice).1);
+            }
+        }
+    }
+
+    word_pool
+}
+
+/// Picks up to `count` due attributes of the active words of `dict`, preferring the ones most
+/// likely forgotten. Attributes never drilled count as forgotten.
+fn pick_due_attributes(
+    dict: &Dictionary,
+    knowledge: &Knowledge,
+    options: &DrillOptions,
+    start_time: DateTime<Utc>,
+) -> Vec<(WordID, String)> {
+    let score_max = knowledge.get_active_words() as u32;
+    let mut pool: Vec<(f32, (WordID, String))> = Vec::new();
+
+    for id in dict.get_words_leq_score(score_max).iter().copied() {
+        if knowledge.is_suspended(id) || knowledge.is_buried(id, start_time) {
+            continue;
+        }
+
+        for attribute in dict.get_word_from_id(id).attributes.iter() {
+            if !options.attributes.is_empty() && !options.attributes.contains(&attribute.name) {
+                continue;
+            }
+
+            let pv = match knowledge.get_attribute_knowledge(id, &attribute.name) {
+                Some(k) => k.calculate_p_value(start_time),
+                None => 0.0,
+            };
+
+            if pv < RECALL_THRESHOLD {
+                pool.push((1.0 - pv, (id, attribute.name.to_owned())));
+            }
+        }
+    }
+
+    pick_weighted(pool, options.limit)
+}
+
+/// Picks up to `count` due cloze cards of the active words of `dict`, each from a random
+/// example that contains the word.
+fn pick_due_cloze(
+    dict: &Dictionary,
+    knowledge: &Knowledge,
+    count: usize,
+    start_time: DateTime<Utc>,
+) -> Vec<(WordID, usize)> {
+    let score_max = knowledge.get_active_words() as u32;
+    let mut rng = rand::thread_rng();
+    let mut pool: Vec<(f32, (WordID, usize))> = Vec::new();
+
+    for id in dict.get_words_leq_score(score_max).iter().copied() {
+        if knowledge.is_suspended(id) || knowledge.is_buried(id, start_time) {
+            continue;
+        }
+
+        let pv = match knowledge.get_cloze_knowledge(id) {
+            Some(k) => k.calculate_p_value(start_time),
+            None => 0.0,
+        };
+        if pv >= RECALL_THRESHOLD {
+            continue;
+        }
+
+        if let Some(example) = dict.cloze_examples(id).choose(&mut rng) {
+            pool.push((1.0 - pv, (id, *example)));
+        }
+    }
+
+    pick_weighted(pool, count)
+}
+
+/// Takes up to `count` entries out of `pool`, by weight.
+fn pick_weighted<T>(mut pool: Vec<(f32, T)>, count: usize) -> Vec<T> {
+    let mut out = Vec::new();
+    while !pool.is_empty() && out.len() < count {
+        let i = pick_by_weight(&pool[..]);
+        out.push(pool.remove(i).1);
+    }
+
+    out
+}
+
 struct PracticeSession {
-    word_pool: Vec<WordID>,
-    knowledge: Knowledge,
+    /// Cards left, with the index of their dictionary's knowledge
+    word_pool: Vec<(usize, WordID, Card)>,
+    /// Knowledge of every dictionary in the session
+    knowledge: Vec<Knowledge>,
     start_time: DateTime<Utc>,
+    /// Answers are not recorded
+    cram: bool,
+    /// Dictionary the words are asked for from, in a pair session
+    prompt: Option<Arc<Dictionary>>,
 }
 
 impl PracticeSession {
     fn new(dict: &Dictionary, knowledge: Knowledge, score_max: u32) -> PracticeSession {
         let start_time = Utc::now();
-        let potential_word_pool: Vec<(f32, WordID)> = dict
-            .get_words_leq_score(score_max)
-            .to_vec()
+        let word_pool = pick_due(dict, &knowledge, score_max, 20, start_time, |_| true)
             .into_iter()
-            .filter_map(|x| {
-                let k = knowledge.get_word_knowledge(x);
-                let pv = k.calculate_p_value(start_time);
-
-                if pv < 0.6 {
-                    Some((1.0 - pv, x))
-                } else {
-                    None
-                }
-            })
+            .map(|id| (0, id, Card::Word))
             .collect();
 
-        let mut word_pool;
-        if potential_word_pool.len() <= 20 {
-            word_pool = potential_word_pool.into_iter().map(|x| x.1).collect();
-        } else {
-            word_pool = Vec::new();
+        PracticeSession {
+            word_pool,
+            knowledge: vec![knowledge],
+            start_time,
+            cram: false,
+            prompt: None,
+        }
+    }
 
-            let mut pool = potential_word_pool.clone();
+    /// A session on the due words of several dictionaries, with at most `quota` words of each.
+    fn mixed(parts: Vec<(Knowledge, usize)>) -> PracticeSession {
+        let start_time = Utc::now();
+        let mut word_pool = Vec::new();
+        let mut knowledge = Vec::new();
 
-            if pool.len() < 100 {
-                // If pool is small, only consider p value
-                for _ in 0..20 {
-                    let choice = {
-                        let i = pick_by_weight(&pool[..]);
-                        pool.remove(i)
-                    };
+        for (i, (knowl, quota)) in parts.into_iter().enumerate() {
+            let dict = knowl.get_dict();
+            let score_max = knowl.get_active_words() as u32;
 
-                    word_pool.push(choice.1);
-                }
-            } else {
-                // If pool is big, also consider word obscurity
-                for _ in 0..20 {
-                    // pick 10
-                    let mut picked = Vec::new();
-                    while picked.len() < 10 {
-                        let choice = pick_by_weight(&pool[..]);
-
-                        if !picked.contains(&choice) {
-                            picked.push(choice);
-                        }
-                    }
+            for id in pick_due(&dict, &knowl, score_max, quota, start_time, |_| true) {
+                word_pool.push((i, id, Card::Word));
+            }
+            knowledge.push(knowl);
+        }
+
+        PracticeSession {
+            word_pool,
+            knowledge,
+            start_time,
+            cram: false,
+            prompt: None,
+        }
+    }
+
+    /// A session on the due words of `dict` that have a linked word in `prompt`. Every linked
+    /// word counts, not only the active ones.
+    fn paired(dict: &Dictionary, knowledge: Knowledge, prompt: Arc<Dictionary>) -> PracticeSession {
+        let start_time = Utc::now();
+        let linked = |id| match &dict.get_word_from_id(id).concept {
+            Some(concept) => !prompt.find_concept(concept).is_empty(),
+            None => false,
+        };
+
+        let word_pool = pick_due(dict, &knowledge, u32::MAX, 20, start_time, linked)
+            .into_iter()
+            .map(|id| (0, id, Card::Word))
+            .collect();
+
+        PracticeSession {
+            word_pool,
+            knowledge: vec![knowledge],
+            start_time,
+            cram: false,
+            prompt: Some(prompt),
+        }
+    }
 
-                    // re-weight based on word obscurity
-                    let max_obs = picked
-                        .iter()
-                        .map(|x| {
-                            let w = dict.get_word_from_id(pool[*x].1);
-
-                            w.obscurity
-                        })
-                        .max()
-                        .unwrap();
-                    let picked: Vec<(f32, WordID)> = picked
-                        .iter()
-                        .map(|x| {
-                            let id = pool[*x].1;
-                            let w = dict.get_word_from_id(id);
-
-                            ((max_obs - w.obscurity + 1) as f32, id)
-                        })
-                        .collect();
-
-                    let choice = pick_by_weight(&picked[..]);
-                    word_pool.push(pool.remove(choice).1);
+    /// A session on the words of each dictionary that match its filter, whether they are due
+    /// or not. `options` gives the limit and whether it is a cram session.
+    fn filtered(parts: Vec<(Knowledge, WordFilter)>, options: &SessionOptions) -> PracticeSession {
+        let start_time = Utc::now();
+        let mut word_pool = Vec::new();
+        let mut knowledge = Vec::new();
+
+        for (i, (knowl, filter)) in parts.into_iter().enumerate() {
+            let dict = knowl.get_dict();
+
+            for id in dict.get_word_ids().iter().copied() {
+                if !knowl.is_suspended(id) && filter.matches(id, &dict, &knowl, start_time) {
+                    word_pool.push((i, id, Card::Word));
                 }
             }
+            knowledge.push(knowl);
+        }
+
+        word_pool.shuffle(&mut rand::thread_rng());
+        if let Some(limit) = options.limit {
+            word_pool.truncate(limit);
         }
 
         PracticeSession {
             word_pool,
             knowledge,
             start_time,
+            cram: options.cram,
+            prompt: None,
+        }
+    }
+
+    /// A session asking for the due attributes of the active words of `dict`.
+    fn drill(dict: &Dictionary, knowledge: Knowledge, options: &DrillOptions) -> PracticeSession {
+        let start_time = Utc::now();
+        let word_pool = pick_due_attributes(dict, &knowledge, options, start_time)
+            .into_iter()
+            .map(|(id, name)| (0, id, Card::Attribute(name)))
+            .collect();
+
+        PracticeSession {
+            word_pool,
+            knowledge: vec![knowledge],
+            start_time,
+            cram: false,
+            prompt: None,
+        }
+    }
+
+    /// A session of cloze cards for the due words of `dict` that occur in their examples.
+    fn cloze(dict: &Dictionary, knowledge: Knowledge, options: &ClozeOptions) -> PracticeSession {
+        let start_time = Utc::now();
+        let word_pool = pick_due_cloze(dict, &knowledge, options.limit, start_time)
+            .into_iter()
+            .map(|(id, example)| (0, id, Card::Cloze(example)))
+            .collect();
+
+        PracticeSession {
+            word_pool,
+            knowledge: vec![knowledge],
+            start_time,
+            cram: false,
+            prompt: None,
         }
     }
 
-    fn pick_word(&mut self) -> WordID {
+    /// Takes a random word out of the pool, so the dictionaries of a mixed session interleave.
+    fn pick_word(&mut self) -> (DictUID, WordID, Card) {
         let mut rng = rand::thread_rng();
 
         let choice = rng.gen_range(0..self.word_pool.len());
+        let (i, id, card) = self.word_pool.remove(choice);
+
+        (self.knowledge[i].get_dict().get_uid(), id, card)
+    }
+
+    fn practice(&mut self, dict: DictUID, word: WordID, card: &Card, result: bool) {
+        if self.cram {
+            return;
+        }
 
-        self.word_pool.remove(choice)
+        if let Some(knowl) = self
+            .knowledge
+            .iter_mut()
+            .find(|k| k.get_dict().get_uid() == dict)
+        {
+            match card {
+                Card::Word => knowl.practice(word, result),
+                Card::Attribute(name) => knowl.practice_attribute(word, name, result),
+                Card::Cloze(_) => knowl.practice_cloze(word, result),
+            }
+        }
     }
 
     fn get_pool_size(&self) -> usize {
         self.word_pool.len()
     }
 
-    fn recover_knowledge(self) -> Knowledge {
+    /// Takes every card of a word out of the pool, if it is there.
+    fn remove_word(&mut self, dict: DictUID, word: WordID) {
+        let knowledge = &self.knowledge;
+
+        self.word_pool
+            .retain(|(i, id, _)| *id != word || knowledge[*i].get_dict().get_uid() != dict);
+    }
+
+    fn recover_knowledge(self) -> Vec<Knowledge> {
         self.knowledge
     }
 }
@@ -163,146 +664,1180 @@ impl PracticeSession {
 pub struct Application {
     user_dir: PathBuf,
     dict_dir: PathBuf,
+    trash: Trash,
     users: HashMap<String, User>,
     dicts: DictMap,
+    dict_files: HashMap<DictUID, PathBuf>,
+    /// Id of each personal dictionary -> name of the user it belongs to
+    personal_dicts: HashMap<DictUID, String>,
     current_dict: Option<DictID>,
     current_user: Option<UserID>,
     practice_session: Option<PracticeSession>,
-    current_word: Option<WordID>,
+    /// Dictionary and id of the word being practiced, and what is asked about it
+    current_word: Option<(DictUID, WordID, Card)>,
     app_handle: AppHandle
 }
 
 impl Application {
-    pub fn new<P1: AsRef<Path>, P2: AsRef<Path>>(
+    pub fn new<P1: AsRef<Path>, P2: AsRef<Path>, P3: AsRef<Path>>(
         user_dir: P1,
         dict_dir: P2,
+        trash_dir: P3,
         app_handle: AppHandle
     ) -> Result<Application, Error> {
         let user_dir = to_dir_path!(user_dir);
         let dict_dir = to_dir_path!(dict_dir);
+        let trash = Trash::new(to_dir_path!(trash_dir));
         let users = HashMap::<String, User>::new();
         let dicts = DictMap::new();
 
-        Ok(Application {
-            user_dir,
-            dict_dir,
-            users,
-            dicts,
-            current_dict: None,
-            current_user: None,
-            practice_session: None,
-            current_word: None,
-            app_handle
-        })
+        Ok(Application {
+            user_dir,
+            dict_dir,
+            trash,
+            users,
+            dicts,
+            dict_files: HashMap::new(),
+            personal_dicts: HashMap::new(),
+            current_dict: None,
+            current_user: None,
+            practice_session: None,
+            current_word: None,
+            app_handle
+        })
+    }
+
+    pub fn load(&mut self, tracker: Option<Progress>) -> Result<(), Error> {
+        // Count Dictionaries
+        let mut dict_files = Vec::new();
+        let mut dict_filesize = 0;
+
+        for r in read_dir(&self.dict_dir)? {
+            let dict_path = r?.path();
+
+            match dict_path.extension() {
+                Some(ex) => match ex.to_str() {
+                    Some("dct") => {
+                        dict_filesize += std::fs::metadata(&dict_path)?.len();
+                        dict_files.push(dict_path);
+                    }
+                    _ => (),
+                },
+                None => (),
+            }
+        }
+
+        // Count Users
+        let mut user_files = Vec::new();
+        let mut user_filesize = 0;
+
+        for r in read_dir(&self.user_dir)? {
+            let user_path = r?.path();
+
+            match user_path.extension() {
+                Some(ex) => match ex.to_str() {
+                    Some("usr") => {
+                        user_filesize += metadata(&user_path)?.len();
+                        user_files.push(user_path);
+                    }
+                    // Personal dictionaries live next to their user
+                    Some("dct") => {
+                        dict_filesize += metadata(&user_path)?.len();
+                        dict_files.push(user_path);
+                    }
+                    _ => (),
+                },
+                None => (),
+            }
+        }
+
+        // Create progress trackers
+        let mut dict_progress = Progress::new(dict_filesize);
+        let mut user_progress = Progress::new(user_filesize);
+
+        match tracker {
+            Some(mut tracker) => tracker.append(&[&dict_progress, &user_progress]),
+            None => (),
+        }
+
+        // Load Dictionaries
+        let dict_prog = 1.0 / (dict_files.len() as f32);
+        for dict_file in dict_files {
+            let mut file = File::open(&dict_file)?;
+
+            let r = Dictionary::load_from(&mut file);
+
+            if r.is_ok() {
+                let (mut dict, file_version) = r.unwrap();
+                drop(file);
+
+                // a copied file shares the id of its original
+                let duplicate = match self.dict_files.get(&dict.get_uid()) {
+                    Some(path) => *path != dict_file,
+                    None => false,
+                };
+                if duplicate {
+                    dict.regenerate_uid();
+                }
+
+                match (file_version, duplicate) {
+                    (FileVersion::Current, false) => (),
+                    (FileVersion::Current, true) => {
+                        println!("duplicate dictionary id, assigning a new one...");
+                        let mut file = File::create(&dict_file)?;
+                        dict.save_to(&mut file)?
+                    }
+                    (FileVersion::Old(_), _) => {
+                        println!("old filetype, converting...");
+                        let mut file = File::create(&dict_file)?;
+                        dict.save_to(&mut file)?
+                    }
+                };
+
+                self.register_dict(dict, dict_file);
+
+                dict_progress.add_progress(dict_prog);
+            } else {
+                println!("{}", r.err().unwrap().msg());
+            }
+        }
+
+        // Load Users
+        let user_prog = 1.0 / (user_files.len() as f32);
+        for user_file in user_files {
+            let mut file = File::open(user_file)?;
+
+            let r = User::load_from(&mut file, &self.dicts);
+
+            if r.is_ok() {
+                let user = r.unwrap();
+                self.users.insert(user.get_name().to_owned(), user);
+
+                user_progress.add_progress(user_prog);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Adds a loaded dictionary. Dictionaries in the user dir are the personal dictionary of the
+    /// user with the same file name.
+    fn register_dict(&mut self, dict: Dictionary, path: PathBuf) -> Arc<Dictionary> {
+        if path.parent() == Some(self.user_dir.as_path()) {
+            let owner = path.file_stem().unwrap().to_str().unwrap();
+            self.personal_dicts.insert(dict.get_uid(), owner.to_owned());
+        }
+
+        let dict = Arc::new(dict);
+        self.dict_files.insert(dict.get_uid(), path);
+        self.dicts.insert(dict.clone());
+
+        dict
+    }
+
+    fn unregister_dict(&mut self, uid: &DictUID) {
+        self.dicts.remove(uid);
+        self.dict_files.remove(uid);
+        self.personal_dicts.remove(uid);
+
+        if self.current_dict.as_ref().map(|d| d.uid) == Some(*uid) {
+            self.current_dict = None;
+        }
+    }
+
+    /// Every dictionary except the personal dictionaries of other users.
+    fn visible_dicts(&self) -> impl Iterator<Item = &Arc<Dictionary>> {
+        let current_user = self.current_user.as_ref().map(|u| u.name.as_str());
+
+        (&self.dicts)
+            .into_iter()
+            .filter(move |x| match self.personal_dicts.get(x.0) {
+                Some(owner) => Some(owner.as_str()) == current_user,
+                None => true,
+            })
+            .map(|x| x.1)
+    }
+
+    /// Ids of removed words of `dict` that any user still has archived knowledge of, including
+    /// the knowledge taken by a running session.
+    fn archived_ids(&self, dict: &Arc<Dictionary>) -> HashSet<WordID> {
+        let session = self
+            .practice_session
+            .iter()
+            .flat_map(|s| s.knowledge.iter());
+
+        self.users
+            .values()
+            .flat_map(|u| u.get_knowledge())
+            .chain(session)
+            .filter(|k| Arc::ptr_eq(&k.get_dict(), dict))
+            .flat_map(|k| k.get_archived_ids())
+            .collect()
+    }
+
+    /// The current user's knowledge of `dict`, including the one taken by a running session.
+    fn find_knowledge(&self, dict: &Arc<Dictionary>) -> Option<&Knowledge> {
+        if let Some(sesh) = &self.practice_session {
+            let i = sesh
+                .knowledge
+                .iter()
+                .position(|k| Arc::ptr_eq(&k.get_dict(), dict));
+            if let Some(i) = i {
+                return Some(&sesh.knowledge[i]);
+            }
+        }
+
+        let user = self.users.get(&self.current_user.as_ref()?.name)?;
+
+        user.get_knowledge()
+            .iter()
+            .find(|k| Arc::ptr_eq(&k.get_dict(), dict))
+    }
+
+    /// The current user's knowledge of `dict`, created if they have none yet.
+    fn find_knowledge_mut(&mut self, dict: &Arc<Dictionary>) -> Result<&mut Knowledge, Error> {
+        if let Some(sesh) = &mut self.practice_session {
+            let i = sesh
+                .knowledge
+                .iter()
+                .position(|k| Arc::ptr_eq(&k.get_dict(), dict));
+            if let Some(i) = i {
+                return Ok(&mut sesh.knowledge[i]);
+            }
+        }
+
+        let user = self
+            .users
+            .get_mut(&self.current_user.as_ref().ok_or("No user selected!")?.name)
+            .ok_or("User not found!")?;
+
+        let i = user
+            .get_knowledge()
+            .iter()
+            .position(|k| Arc::ptr_eq(&k.get_dict(), dict));
+
+        let i = match i {
+            Some(i) => i,
+            None => {
+                user.add_knowledge(Knowledge::create(dict.clone()));
+                user.get_knowledge().len() - 1
+            }
+        };
+
+        Ok(&mut user.get_knowledge_mut()[i])
+    }
+
+    pub fn get_dict_list(&self) -> Box<[DictInfo]> {
+        let list: Box<[DictInfo]> = self
+            .visible_dicts()
+            .map(|x| DictInfo {
+                id: DictID::of(x),
+                word_count: x.get_word_ids().len(),
+                metadata: x.get_metadata().clone(),
+            })
+            .collect();
+
+        list
+    }
+
+    /// Searches headwords, pronunciations and definitions of every visible dictionary. Hits are
+    /// ranked by how well they match, then by how common the word is.
+    pub fn search(&self, query: &str, options: &SearchOptions) -> Box<[SearchHit]> {
+        let now = Utc::now();
+        let mut hits = Vec::new();
+
+        for dict in self.visible_dicts() {
+            let knowl = self.find_knowledge(dict);
+
+            for m in dict.search(query, options) {
+                hits.push(SearchHit {
+                    dict: DictID::of(dict),
+                    id: m.id,
+                    word: dict.get_word_from_id(m.id).clone().into(),
+                    field: m.field,
+                    kind: m.kind,
+                    score: m.score,
+                    status: knowl.and_then(|k| k.get_status(m.id, now)),
+                });
+            }
+        }
+
+        hits.sort_by(|a, b| {
+            b.score
+                .cmp(&a.score)
+                .then(a.word.obscurity.cmp(&b.word.obscurity))
+                .then(a.word.text.cmp(&b.word.text))
+        });
+        hits.truncate(options.limit);
+
+        hits.into_boxed_slice()
+    }
+
+    /// Saves a rebuilt dictionary over the file of the one it replaces, points every loaded
+    /// user's knowledge at it and saves the users. Words keep their knowledge through their ids.
+    fn replace_dict(&mut self, dict: Dictionary) -> Result<(), Error> {
+        if self.practice_session.is_some() {
+            return Err("Finish the practice session first!")?;
+        }
+
+        self.install_dict(dict)
+    }
+
+    /// Saves `dict` over the dictionary with its UID and moves all knowledge over to it, that of
+    /// a running session too. Every word of the session has to still be in `dict`.
+    fn install_dict(&mut self, dict: Dictionary) -> Result<(), Error> {
+        let old = self
+            .dicts
+            .get(&dict.get_uid())
+            .ok_or("Dict not found!")?
+            .clone();
+
+        let renamed = dict.get_title() != old.get_title();
+        if renamed && self.dicts.find_by_title(dict.get_title()).is_some() {
+            return Err("A dictionary with this title already exists!")?;
+        }
+
+        let path = self
+            .dict_files
+            .get(&dict.get_uid())
+            .ok_or("Dict not found!")?;
+        let mut file = File::create(path)?;
+        dict.save_to(&mut file)?;
+
+        let dict = Arc::new(dict);
+        self.dicts.insert(dict.clone());
+
+        for user in self.users.values_mut() {
+            for k in user.get_knowledge_mut() {
+                if Arc::ptr_eq(&k.get_dict(), &old) {
+                    k.set_dict(dict.clone());
+                }
+            }
+        }
+
+        if let Some(sesh) = &mut self.practice_session {
+            for k in &mut sesh.knowledge {
+                if Arc::ptr_eq(&k.get_dict(), &old) {
+                    k.set_dict(dict.clone());
+                }
+            }
+            if let Some(prompt) = &mut sesh.prompt {
+                if Arc::ptr_eq(prompt, &old) {
+                    *prompt = dict.clone();
+                }
+            }
+        }
+
+        if let Some(current) = &mut self.current_dict {
+            if current.uid == dict.get_uid() {
+                current.name = dict.get_title().to_owned();
+            }
+        }
+
+        self.save_all_users()
+    }
+
+    fn get_dict(&self, dict: &DictID) -> Result<Arc<Dictionary>, Error> {
+        Ok(self.dicts.get(&dict.uid).ok_or("Dict not found!")?.clone())
+    }
+
+    /// Replaces a dictionary with a new version of itself, carrying every loaded user's
+    /// knowledge over to the words that are still there.
+    pub fn update_dict(&mut self, dict: DictID, new: Dictionary) -> Result<DictDiff, Error> {
+        let old = self.get_dict(&dict)?;
+
+        let (updated, diff) = old.updated_with(new, &self.archived_ids(&old));
+        self.replace_dict(updated)?;
+
+        Ok(diff)
+    }
+
+    pub fn add_word(&mut self, dict: DictID, word: Word) -> Result<WordID, Error> {
+        let old = self.get_dict(&dict)?;
+
+        let (updated, id) = old.with_word_added(word, &self.archived_ids(&old));
+        self.replace_dict(updated)?;
+
+        Ok(id)
+    }
+
+    pub fn edit_word(&mut self, dict: DictID, id: WordID, word: Word) -> Result<(), Error> {
+        let old = self.get_dict(&dict)?;
+
+        let updated = old.with_word_replaced(id, word)?;
+        self.replace_dict(updated)
+    }
+
+    pub fn delete_word(&mut self, dict: DictID, id: WordID) -> Result<(), Error> {
+        let old = self.get_dict(&dict)?;
+
+        let updated = old.without_word(id)?;
+        self.replace_dict(updated)
+    }
+
+    /// Appends `text` to the current user's personal dictionary, creating it if needed.
+    /// The word is ranked last and made active, so it shows up in the next session.
+    pub fn mine_word(&mut self, text: String) -> Result<MinedWord, Error> {
+        let text = text.trim().to_owned();
+        if text.is_empty() {
+            return Err("Nothing to add!")?;
+        }
+
+        let user_name = self
+            .current_user
+            .as_ref()
+            .ok_or("No user selected!")?
+            .name
+            .clone();
+        let dict = self.personal_dict(&user_name)?;
+
+        let obscurity = dict.get_max_obscurity() + 1;
+        let word = Word {
+            text,
+            definition: String::new(),
+            pronunciation: None,
+            obscurity,
+            audio: None,
+            image: None,
+            part_of_speech: None,
+            senses: Vec::new(),
+            examples: Vec::new(),
+            synonyms: Vec::new(),
+            antonyms: Vec::new(),
+            tags: Vec::new(),
+            concept: None,
+            attributes: Vec::new(),
+        };
+
+        // adding a word leaves the words of a running session alone
+        let (updated, id) = dict.with_word_added(word, &self.archived_ids(&dict));
+        self.install_dict(updated)?;
+
+        let dict = self.dicts[&dict.get_uid()].clone();
+        let knowl = self.find_knowledge_mut(&dict)?;
+
+        if knowl.get_active_words() < obscurity as usize {
+            knowl.set_active_words(obscurity as usize);
+        }
+        self.save_current_user()?;
+
+        Ok(MinedWord {
+            dict: DictID::of(&dict),
+            id,
+            word: dict.get_word_from_id(id).clone().into(),
+        })
+    }
+
+    fn personal_dict_title(user_name: &str) -> String {
+        format!("{}'s mined words", user_name)
+    }
+
+    fn find_personal_dict(&self, user_name: &str) -> Option<DictUID> {
+        self.personal_dicts
+            .iter()
+            .find(|(_, owner)| owner.as_str() == user_name)
+            .map(|(uid, _)| *uid)
+    }
+
+    fn personal_dict(&mut self, user_name: &str) -> Result<Arc<Dictionary>, Error> {
+        if let Some(uid) = self.find_personal_dict(user_name) {
+            return Ok(self.dicts[&uid].clone());
+        }
+
+        let title = Application::personal_dict_title(user_name);
+        let dict = Dictionary::create(Box::new([]), title, ObscurityMode::Manual);
+
+        let mut dict_path = PathBuf::new();
+        dict_path.push(&self.user_dir);
+        dict_path.push(user_name.to_owned() + ".dct");
+        let mut dict_file = File::create(&dict_path)?;
+        dict.save_to(&mut dict_file)?;
+
+        Ok(self.register_dict(dict, dict_path))
+    }
+
+    pub fn set_dict_title(&mut self, dict: DictID, title: String) -> Result<DictID, Error> {
+        if title.is_empty() {
+            return Err("Must provide a title!")?;
+        }
+
+        let old = self.get_dict(&dict)?;
+
+        self.replace_dict(old.with_title(title.to_owned()))?;
+
+        Ok(DictID {
+            uid: dict.uid,
+            name: title,
+        })
+    }
+
+    /// Adds the inflected forms of a TSV lemma table to a dictionary, see
+    /// `Dictionary::with_lemma_table`.
+    pub fn import_lemmas<P: AsRef<Path>>(
+        &mut self,
+        dict: DictID,
+        file: P,
+        replace: bool,
+    ) -> Result<LemmaReport, Error> {
+        let old = self.get_dict(&dict)?;
+        let table = std::fs::read_to_string(file)?;
+
+        let (new, report) = old.with_lemma_table(&table, replace)?;
+        self.replace_dict(new)?;
+
+        Ok(report)
+    }
+
+    /// Re-ranks a dictionary's obscurity by how often its words occur in a folder of text files.
+    pub fn analyze_corpus<P: AsRef<Path>>(&mut self, dict: DictID, folder: P) -> Result<(), Error> {
+        let old = self.get_dict(&dict)?;
+
+        let counts = corpus::count_headwords(folder, &old)?;
+        self.replace_dict(old.rerank_by_frequency(&counts))
+    }
+
+    /// Moves a dictionary to the trash. Every user's progress on it is archived next to it and
+    /// comes back when the dictionary is restored.
+    pub fn delete_dict(&mut self, dict: DictID) -> Result<TrashEntry, Error> {
+        if self.practice_session.is_some() {
+            return Err("Finish the practice session first!")?;
+        }
+
+        let old = self.get_dict(&dict)?;
+        let path = self
+            .dict_files
+            .get(&dict.uid)
+            .ok_or("Dict not found!")?
+            .clone();
+        let name = path
+            .file_stem()
+            .and_then(|n| n.to_str())
+            .ok_or("Invalid file name!")?;
+
+        let (entry, folder) = self.trash.create_entry(TrashKind::Dict, name)?;
+
+        for user in self.users.values() {
+            for k in user.get_knowledge() {
+                if Arc::ptr_eq(&k.get_dict(), &old) {
+                    let mut file = File::create(folder.join(user.get_name().to_owned() + ".knw"))?;
+                    k.save_to(&mut file)?;
+                }
+            }
+        }
+
+        let media = media::media_dir(&path);
+        if media.is_dir() {
+            move_into(&media, &folder)?;
+        }
+        move_into(&path, &folder)?;
+
+        for user in self.users.values_mut() {
+            let archived: Vec<*const Knowledge> = user
+                .get_knowledge()
+                .iter()
+                .filter(|k| Arc::ptr_eq(&k.get_dict(), &old))
+                .map(|k| k as *const Knowledge)
+                .collect();
+
+            for k in archived {
+                user.take_knowledge(k);
+            }
+        }
+
+        self.unregister_dict(&dict.uid);
+        self.save_all_users()?;
+
+        Ok(entry)
+    }
+
+    /// Moves a user and their personal dictionary to the trash.
+    pub fn delete_user(&mut self, user: UserID) -> Result<TrashEntry, Error> {
+        if self.practice_session.is_some() {
+            return Err("Finish the practice session first!")?;
+        }
+
+        let u = self.users.get(&user.name).ok_or("User not found!")?;
+        self.save_user(u)?;
+
+        let (entry, folder) = self.trash.create_entry(TrashKind::User, &user.name)?;
+
+        move_into(&self.user_path(&user.name), &folder)?;
+
+        if let Some(uid) = self.find_personal_dict(&user.name) {
+            move_into(&self.dict_files[&uid], &folder)?;
+            self.unregister_dict(&uid);
+        }
+
+        self.users.remove(&user.name);
+
+        if self.current_user.as_ref().map(|u| u.name.as_str()) == Some(user.name.as_str()) {
+            self.current_user = None;
+        }
+
+        Ok(entry)
+    }
+
+    /// Renames a user, their file and their personal dictionary.
+    pub fn rename_user(&mut self, user: UserID, name: String) -> Result<UserID, Error> {
+        if self.practice_session.is_some() {
+            return Err("Finish the practice session first!")?;
+        }
+        if name.is_empty() {
+            return Err("Must provide a name!")?;
+        }
+        if self.users.contains_key(&name) {
+            return Err("User with this name already exists!")?;
+        }
+
+        let mut u = self.users.remove(&user.name).ok_or("User not found!")?;
+        u.set_name(name.to_owned());
+        self.save_user(&u)?;
+        remove_file(self.user_path(&user.name))?;
+        self.users.insert(name.to_owned(), u);
+
+        if let Some(uid) = self.find_personal_dict(&user.name) {
+            let path = self.user_dir.join(name.to_owned() + ".dct");
+            rename(&self.dict_files[&uid], &path)?;
+            self.dict_files.insert(uid, path);
+            self.personal_dicts.insert(uid, name.to_owned());
+
+            let dict = self.dicts[&uid].clone();
+            if dict.get_title() == Application::personal_dict_title(&user.name) {
+                self.replace_dict(dict.with_title(Application::personal_dict_title(&name)))?;
+            }
+        }
+
+        let id = UserID { name };
+        if self.current_user.as_ref().map(|u| u.name.as_str()) == Some(user.name.as_str()) {
+            self.set_current_user(Some(id.clone()));
+        }
+
+        Ok(id)
+    }
+
+    pub fn get_trash(&self) -> Result<Box<[TrashEntry]>, Error> {
+        self.trash.get_entries()
+    }
+
+    /// Moves a deleted dictionary or user back out of the trash.
+    pub fn restore_from_trash(&mut self, id: String) -> Result<(), Error> {
+        if self.practice_session.is_some() {
+            return Err("Finish the practice session first!")?;
+        }
+
+        let (entry, folder) = self.trash.get_entry(&id)?;
+
+        match entry.kind {
+            TrashKind::Dict => self.restore_dict(&folder)?,
+            TrashKind::User => self.restore_user(&folder)?,
+        }
+
+        self.trash.remove_entry(&id)
+    }
+
+    pub fn empty_trash(&self) -> Result<(), Error> {
+        self.trash.empty()
+    }
+
+    fn files_with_extension(folder: &Path, extension: &str) -> Result<Vec<PathBuf>, Error> {
+        let mut out = Vec::new();
+
+        for r in read_dir(folder)? {
+            let path = r?.path();
+            if path.extension().and_then(|e| e.to_str()) == Some(extension) {
+                out.push(path);
+            }
+        }
+
+        Ok(out)
+    }
+
+    /// Loads a dictionary file from the trash, refusing it if it is installed again already.
+    fn load_trashed_dict(&self, path: &Path) -> Result<(Dictionary, FileVersion), Error> {
+        let mut file = File::open(path)?;
+        let (dict, version) = Dictionary::load_from(&mut file)?;
+
+        if self.dicts.contains(&dict.get_uid()) {
+            return Err("This dictionary is installed already!")?;
+        }
+
+        Ok((dict, version))
+    }
+
+    fn restore_dict(&mut self, folder: &Path) -> Result<(), Error> {
+        let path = Application::files_with_extension(folder, "dct")?
+            .pop()
+            .ok_or("Trash entry has no dictionary!")?;
+        let (dict, version) = self.load_trashed_dict(&path)?;
+
+        // personal dictionaries go back to their user if they still exist
+        let stem = path.file_stem().and_then(|n| n.to_str()).unwrap_or("");
+        let personal = self.users.contains_key(stem)
+            && self.find_personal_dict(stem).is_none()
+            && dict.get_title() == Application::personal_dict_title(stem);
+        let dir = if personal {
+            self.user_dir.clone()
+        } else {
+            self.dict_dir.clone()
+        };
+
+        if dir.join(path.file_name().unwrap()).exists() {
+            return Err("A dictionary file with this name exists already!")?;
+        }
+
+        let media = media::media_dir(&path);
+        if media.is_dir() {
+            move_into(&media, &dir)?;
+        }
+        let path = move_into(&path, &dir)?;
+        if let FileVersion::Old(_) = version {
+            let mut file = File::create(&path)?;
+            dict.save_to(&mut file)?;
+        }
+        let dict = self.register_dict(dict, path);
+
+        for user in self.users.values_mut() {
+            user.reattach_knowledge(&self.dicts)?;
+        }
+        for path in Application::files_with_extension(folder, "knw")? {
+            let name = path.file_stem().and_then(|n| n.to_str()).unwrap_or("");
+            let user = match self.users.get_mut(name) {
+                Some(user) => user,
+                None => continue,
+            };
+
+            if user
+                .get_knowledge()
+                .iter()
+                .any(|k| Arc::ptr_eq(&k.get_dict(), &dict))
+            {
+                continue;
+            }
+
+            let mut file = File::open(&path)?;
+            let knowledge = Knowledge::load_from(&mut file, &self.dicts)?;
+            user.add_knowledge(knowledge.ok_or("Dict not found!")?);
+        }
+
+        self.save_all_users()
+    }
+
+    fn restore_user(&mut self, folder: &Path) -> Result<(), Error> {
+        let user_path = Application::files_with_extension(folder, "usr")?
+            .pop()
+            .ok_or("Trash entry has no user!")?;
+
+        // the user's knowledge may point at its personal dictionary, so that has to be loaded first
+        let personal = match Application::files_with_extension(folder, "dct")?.pop() {
+            Some(path) => {
+                let (dict, version) = self.load_trashed_dict(&path)?;
+                let uid = dict.get_uid();
+
+                let dict = self.register_dict(dict, self.user_dir.join(path.file_name().unwrap()));
+                Some((path, dict, version, uid))
+            }
+            None => None,
+        };
+
+        let mut file = File::open(&user_path)?;
+        let user = User::load_from(&mut file, &self.dicts).and_then(|user| {
+            if self.users.contains_key(user.get_name()) {
+                return Err("User with this name already exists!")?;
+            }
+
+            Ok(user)
+        });
+        let user = match user {
+            Ok(user) => user,
+            Err(e) => {
+                if let Some((_, _, _, uid)) = personal {
+                    self.unregister_dict(&uid);
+                }
+                return Err(e);
+            }
+        };
+        drop(file);
+
+        if let Some((path, dict, version, _)) = personal {
+            let path = move_into(&path, &self.user_dir)?;
+            if let FileVersion::Old(_) = version {
+                let mut file = File::create(&path)?;
+                dict.save_to(&mut file)?;
+            }
+        }
+
+        move_into(&user_path, &self.user_dir)?;
+        self.users.insert(user.get_name().to_owned(), user);
+
+        Ok(())
+    }
+
+    /// Lists one page of a dictionary's words together with the current user's progress.
+    pub fn browse_dict(
+        &self,
+        dict: DictID,
+        sort: BrowseSort,
+        descending: bool,
+        page: usize,
+        page_size: usize,
+    ) -> Result<DictPage, Error> {
+        if page_size == 0 {
+            return Err("Page size must not be 0!")?;
+        }
+
+        let dict = self.get_dict(&dict)?;
+        let knowl = self.find_knowledge(&dict);
+        let now = Utc::now();
+
+        let mut words: Vec<(WordID, &Word, Option<WordStatus>)> = dict
+            .get_word_ids()
+            .iter()
+            .map(|id| {
+                let status = knowl.and_then(|k| k.get_status(*id, now));
+                (*id, dict.get_word_from_id(*id), status)
+            })
+            .collect();
+
+        match sort {
+            BrowseSort::Obscurity => words.sort_by(|a, b| {
+                a.1.obscurity
+                    .cmp(&b.1.obscurity)
+                    .then(a.1.text.cmp(&b.1.text))
+            }),
+            BrowseSort::Alphabetical => {
+                words.sort_by_cached_key(|w| (dict.normalize(&w.1.text), w.1.obscurity))
+            }
+            BrowseSort::Due => words.sort_by_key(|w| {
+                let due = w.2.as_ref().and_then(|s| s.due);
+                (due.is_none(), due, w.1.obscurity)
+            }),
+            BrowseSort::Recall => words.sort_by(|a, b| {
+                let recall = |w: &(WordID, &Word, Option<WordStatus>)| {
+                    w.2.as_ref().map_or(0.0, |s| s.recall)
+                };
+
+                recall(a)
+                    .partial_cmp(&recall(b))
+                    .unwrap_or(Ordering::Equal)
+                    .then(a.1.obscurity.cmp(&b.1.obscurity))
+            }),
+        }
+
+        if descending {
+            words.reverse();
+        }
+
+        let total = words.len();
+        let page_count = total / page_size + (total % page_size != 0) as usize;
+
+        let words = words
+            .into_iter()
+            .skip(page.saturating_mul(page_size))
+            .take(page_size)
+            .map(|(id, word, status)| BrowsedWord {
+                id,
+                word: word.clone().into(),
+                status,
+            })
+            .collect();
+
+        Ok(DictPage {
+            page,
+            page_count,
+            total,
+            words,
+        })
+    }
+
+    /// Leaves a word out of the current user's practice sessions, or brings it back.
+    pub fn set_word_suspended(
+        &mut self,
+        dict: DictID,
+        id: WordID,
+        suspended: bool,
+    ) -> Result<(), Error> {
+        let dict = self.get_dict(&dict)?;
+        if !dict.contains_id(id) {
+            return Err("Word not found!")?;
+        }
+
+        self.find_knowledge_mut(&dict)?.set_suspended(id, suspended);
+
+        if self.practice_session.is_none() {
+            self.save_current_user()?;
+        }
+
+        Ok(())
+    }
+
+    fn get_user(&self) -> Result<&User, Error> {
+        Ok(self
+            .users
+            .get(&self.current_user.as_ref().ok_or("No user selected!")?.name)
+            .ok_or("User not found!")?)
+    }
+
+    fn get_user_mut(&mut self) -> Result<&mut User, Error> {
+        Ok(self
+            .users
+            .get_mut(&self.current_user.as_ref().ok_or("No user selected!")?.name)
+            .ok_or("User not found!")?)
+    }
+
+    /// The current user's note on a word.
+    pub fn get_word_note(&self, dict: DictID, id: WordID) -> Result<Option<String>, Error> {
+        Ok(self
+            .get_user()?
+            .get_note(dict.uid, id)
+            .map(|n| n.to_owned()))
+    }
+
+    /// Sets the current user's note on a word, an empty note removes it.
+    pub fn set_word_note(&mut self, dict: DictID, id: WordID, note: String) -> Result<(), Error> {
+        let dict = self.get_dict(&dict)?;
+        if !dict.contains_id(id) {
+            return Err("Word not found!")?;
+        }
+
+        self.get_user_mut()?.set_note(dict.get_uid(), id, note);
+
+        if self.practice_session.is_none() {
+            self.save_current_user()?;
+        }
+
+        Ok(())
+    }
+
+    /// The current user's override of a word.
+    pub fn get_word_override(
+        &self,
+        dict: DictID,
+        id: WordID,
+    ) -> Result<Option<WordOverride>, Error> {
+        let dict = self.get_dict(&dict)?;
+        if !dict.contains_id(id) {
+            return Err("Word not found!")?;
+        }
+
+        Ok(self.get_user()?.get_override(&dict, id).cloned())
+    }
+
+    /// Overrides the definition or pronunciation of a word for the current user. Leaving both
+    /// out removes the override.
+    pub fn set_word_override(
+        &mut self,
+        dict: DictID,
+        id: WordID,
+        definition: Option<String>,
+        pronunciation: Option<String>,
+    ) -> Result<(), Error> {
+        let dict = self.get_dict(&dict)?;
+        if !dict.contains_id(id) {
+            return Err("Word not found!")?;
+        }
+
+        let definition = definition.filter(|d| !d.trim().is_empty());
+        let pronunciation = pronunciation.filter(|p| !p.trim().is_empty());
+        let o = match (&definition, &pronunciation) {
+            (None, None) => None,
+            _ => Some(WordOverride {
+                headword: dict.get_word_from_id(id).text.to_owned(),
+                definition,
+                pronunciation,
+            }),
+        };
+
+        self.get_user_mut()?.set_override(&dict, id, o);
+
+        if self.practice_session.is_none() {
+            self.save_current_user()?;
+        }
+
+        Ok(())
+    }
+
+    /// Names of the current user's word lists.
+    pub fn get_word_lists(&self) -> Result<Box<[String]>, Error> {
+        Ok(self.get_user()?.get_lists().map(|n| n.to_owned()).collect())
+    }
+
+    /// Words of a list of the current user. Words of dictionaries that are gone are left out.
+    pub fn get_word_list(&self, name: &str) -> Result<Box<[ListedWord]>, Error> {
+        let list = self.get_user()?.get_list(name).ok_or("List not found!")?;
+
+        Ok(list
+            .iter()
+            .filter_map(|(uid, id)| {
+                let dict = self.visible_dicts().find(|d| d.get_uid() == *uid)?;
+                if !dict.contains_id(*id) {
+                    return None;
+                }
+
+                Some(ListedWord {
+                    dict: DictID::of(dict),
+                    id: *id,
+                    word: dict.get_word_from_id(*id).clone().into(),
+                })
+            })
+            .collect())
     }
 
-    pub fn load(&mut self, tracker: Option<Progress>) -> Result<(), Error> {
-        // Count Dictionaries
-        let mut dict_files = Vec::new();
-        let mut dict_filesize = 0;
+    /// Lists of the current user that contain a word.
+    pub fn get_lists_of_word(&self, dict: DictID, id: WordID) -> Result<Box<[String]>, Error> {
+        let user = self.get_user()?;
 
-        for r in read_dir(&self.dict_dir)? {
-            let dict_path = r?.path();
+        Ok(user
+            .get_lists()
+            .filter(|name| {
+                user.get_list(name)
+                    .map_or(false, |list| list.contains(&(dict.uid, id)))
+            })
+            .map(|name| name.to_owned())
+            .collect())
+    }
 
-            match dict_path.extension() {
-                Some(ex) => match ex.to_str() {
-                    Some("dct") => {
-                        dict_filesize += std::fs::metadata(&dict_path)?.len();
-                        dict_files.push(dict_path);
-                    }
-                    _ => (),
-                },
-                None => (),
-            }
+    pub fn create_word_list(&mut self, name: String) -> Result<(), Error> {
+        let name = name.trim().to_owned();
+        if name.is_empty() {
+            return Err("Must provide a name!")?;
         }
 
-        // Count Users
-        let mut user_files = Vec::new();
-        let mut user_filesize = 0;
+        if !self.get_user_mut()?.create_list(name) {
+            return Err("A list with this name exists already!")?;
+        }
 
-        for r in read_dir(&self.user_dir)? {
-            let user_path = r?.path();
+        self.save_lists()
+    }
 
-            match user_path.extension() {
-                Some(ex) => match ex.to_str() {
-                    Some("usr") => {
-                        user_filesize += metadata(&user_path)?.len();
-                        user_files.push(user_path);
-                    }
-                    _ => (),
-                },
-                None => (),
-            }
+    pub fn delete_word_list(&mut self, name: &str) -> Result<(), Error> {
+        if !self.get_user_mut()?.delete_list(name) {
+            return Err("List not found!")?;
         }
 
-        // Create progress trackers
-        let mut dict_progress = Progress::new(dict_filesize);
-        let mut user_progress = Progress::new(user_filesize);
+        self.save_lists()
+    }
 
-        match tracker {
-            Some(mut tracker) => tracker.append(&[&dict_progress, &user_progress]),
-            None => (),
+    /// Adds a word to a list of the current user, or takes it off.
+    pub fn set_word_listed(
+        &mut self,
+        name: &str,
+        dict: DictID,
+        id: WordID,
+        listed: bool,
+    ) -> Result<(), Error> {
+        let dict = self.get_dict(&dict)?;
+        if !dict.contains_id(id) {
+            return Err("Word not found!")?;
         }
 
-        // Load Dictionaries
-        let dict_prog = 1.0 / (dict_files.len() as f32);
-        for dict_file in dict_files {
-            let mut file = File::open(&dict_file)?;
+        let user = self.get_user_mut()?;
+        let found = if listed {
+            user.add_to_list(name, dict.get_uid(), id)
+        } else {
+            user.remove_from_list(name, dict.get_uid(), id)
+        };
+        if !found {
+            return Err("List not found!")?;
+        }
 
-            let r = Dictionary::load_from(&mut file);
+        self.save_lists()
+    }
 
-            if r.is_ok() {
-                let (dict, file_version) = r.unwrap();
-                drop(file);
+    pub fn set_word_starred(
+        &mut self,
+        dict: DictID,
+        id: WordID,
+        starred: bool,
+    ) -> Result<(), Error> {
+        self.set_word_listed(STARRED_LIST, dict, id, starred)
+    }
 
-                match file_version {
-                    FileVersion::Current => (),
-                    FileVersion::Old(_) => {
-                        println!("old filetype, converting...");
-                        let mut file = File::create(dict_file)?;
-                        dict.save_to(&mut file)?
-                    }
-                };
+    pub fn star_current_word(&mut self, starred: bool) -> Result<(), Error> {
+        let (uid, id, _) = self
+            .current_word
+            .as_ref()
+            .ok_or("No word being practiced!")?;
+        let (uid, id) = (*uid, *id);
 
-                self.dicts.insert(Arc::new(dict));
+        let user = self.get_user_mut()?;
+        if starred {
+            user.add_to_list(STARRED_LIST, uid, id);
+        } else {
+            user.remove_from_list(STARRED_LIST, uid, id);
+        }
 
-                dict_progress.add_progress(dict_prog);
-            } else {
-                println!("{}", r.err().unwrap().msg());
-            }
+        self.save_lists()
+    }
+
+    /// Saves list changes, unless a session holds some of the user's knowledge.
+    fn save_lists(&mut self) -> Result<(), Error> {
+        if self.practice_session.is_none() {
+            self.save_current_user()?;
         }
 
-        // Load Users
-        let user_prog = 1.0 / (user_files.len() as f32);
-        for user_file in user_files {
-            let mut file = File::open(user_file)?;
+        Ok(())
+    }
 
-            let r = User::load_from(&mut file, &self.dicts);
+    /// Path of a media file of a dictionary, `None` if there is no such file.
+    pub fn get_media_path(&self, dict: DictUID, name: &str) -> Option<PathBuf> {
+        if !media::is_valid_name(name) {
+            return None;
+        }
 
-            if r.is_ok() {
-                let user = r.unwrap();
-                self.users.insert(user.get_name().to_owned(), user);
+        let path = media::media_dir(self.dict_files.get(&dict)?).join(name);
 
-                user_progress.add_progress(user_prog);
-            }
+        if path.is_file() {
+            Some(path)
+        } else {
+            None
         }
+    }
 
-        Ok(())
+    /// Copies the media of a dictionary that is being imported or updated into its media folder.
+    pub fn install_media(&self, uid: DictUID, names: &[String], from: &Path) -> Result<(), Error> {
+        let path = self.dict_files.get(&uid).ok_or("Dict not found!")?;
+
+        media::copy_files(names, from, &media::media_dir(path))
     }
 
-    pub fn get_dict_list(&self) -> Box<[DictID]> {
-        let list: Box<[DictID]> = (&self.dicts)
-            .into_iter()
-            .map(|x| DictID {
-                name: x.1.get_title().to_owned(),
-            })
-            .collect();
+    /// Writes a dictionary and its media folder into `folder`, as a bundle that can be imported.
+    pub fn export_dict<P: AsRef<Path>>(&self, dict: DictID, folder: P) -> Result<PathBuf, Error> {
+        let folder = to_dir_path!(folder);
+        let path = self.dict_files.get(&dict.uid).ok_or("Dict not found!")?;
 
-        list
+        let to = folder.join(path.file_name().unwrap());
+        if to.exists() {
+            Err("A file with this name exists already!")?;
+        }
+
+        std::fs::copy(path, &to)?;
+        media::copy_dir(&media::media_dir(path), &media::media_dir(&to))?;
+
+        Ok(to)
+    }
+
+    /// Dictionary of the word being practiced, otherwise the selected one.
+    pub fn get_current_dict(&self) -> Option<DictID> {
+        match self.current_word {
+            Some((uid, _, _)) => self.dicts.get(&uid).map(|d| DictID::of(d)),
+            None => self.current_dict.clone(),
+        }
+    }
+
+    /// `path` is the file `dict` was read from.
+    pub fn preview_dict(
+        &self,
+        dict: &Dictionary,
+        empty_fields: Vec<EmptyField>,
+        path: &Path,
+    ) -> DictPreview {
+        let file_collision = self.installed_path(path).exists();
+
+        DictPreview::create(dict, &self.dicts, empty_fields, file_collision)
     }
 
     pub fn get_pool_size(&self, dict: DictID) -> usize {
-        let dict = &self.dicts[&dict.name];
+        let dict = &self.dicts[&dict.uid];
         let user = self
             .users
             .get(&self.current_user.as_ref().unwrap().name)
@@ -329,10 +1864,14 @@ impl Application {
             .to_vec()
             .into_iter()
             .filter_map(|x| {
+                if knowl.is_suspended(x) || knowl.is_buried(x, start_time) {
+                    return None;
+                }
+
                 let k = knowl.get_word_knowledge(x);
                 let pv = k.calculate_p_value(start_time);
 
-                if pv < 0.6 {
+                if pv < RECALL_THRESHOLD {
                     Some((1.0 - pv, x))
                 } else {
                     None
@@ -342,12 +1881,215 @@ impl Application {
             .len()
     }
 
+    /// Number of words of `dict` a custom session with `filter` could draw from.
+    pub fn count_filtered_words(&self, dict: DictID, filter: &WordFilter) -> usize {
+        let dict = match self.dicts.get(&dict.uid) {
+            Some(dict) => dict.clone(),
+            None => return 0,
+        };
+
+        let fresh;
+        let knowl = match self.find_knowledge(&dict) {
+            Some(k) => k,
+            None => {
+                fresh = Knowledge::create(dict.clone());
+                &fresh
+            }
+        };
+
+        let now = Utc::now();
+
+        dict.get_word_ids()
+            .iter()
+            .filter(|id| !knowl.is_suspended(**id) && filter.matches(**id, &dict, knowl, now))
+            .count()
+    }
+
     pub fn set_dict(&mut self, dict: DictID) {
         self.current_dict = Some(dict);
     }
 
     pub fn start_practice_session(&mut self) -> bool {
-        if self.current_user.is_none() || self.current_dict.is_none() {
+        let dict = match &self.current_dict {
+            Some(dict) => self.dicts[&dict.uid].clone(),
+            None => return false,
+        };
+
+        self.start_session(dict, |dict, knowl| {
+            let s = knowl.get_active_words() as u32;
+            PracticeSession::new(dict, knowl, s)
+        })
+    }
+
+    /// Starts a session on the words of the current dictionary that match `options`.
+    ///
+    /// With `options.list` the session takes the list's words of every visible dictionary
+    /// instead, and `options.filter.words` can only narrow down the words of the current one.
+    pub fn start_custom_session(&mut self, options: SessionOptions) -> bool {
+        let current = self.current_dict.as_ref().map(|d| d.uid);
+
+        let picked: Vec<(Arc<Dictionary>, WordFilter)> = match &options.list {
+            Some(name) => {
+                let list = match self.get_user().ok().and_then(|u| u.get_list(name)) {
+                    Some(list) => list,
+                    None => return false,
+                };
+
+                self.visible_dicts()
+                    .filter_map(|dict| {
+                        let uid = dict.get_uid();
+                        let listed: Vec<WordID> = list
+                            .iter()
+                            .filter(|(d, _)| *d == uid)
+                            .map(|(_, id)| *id)
+                            .filter(|id| match &options.filter.words {
+                                Some(words) if Some(uid) == current => words.contains(id),
+                                Some(_) => false,
+                                None => true,
+                            })
+                            .collect();
+
+                        if listed.is_empty() {
+                            return None;
+                        }
+
+                        let mut filter = options.filter.clone();
+                        filter.words = Some(listed);
+                        Some((dict.clone(), filter))
+                    })
+                    .collect()
+            }
+            None => match current {
+                Some(uid) => vec![(self.dicts[&uid].clone(), options.filter.clone())],
+                None => return false,
+            },
+        };
+
+        let user = match self.current_user.as_ref() {
+            Some(user) => self.users.get_mut(&user.name).unwrap(),
+            None => return false,
+        };
+
+        let parts = picked
+            .into_iter()
+            .map(|(dict, filter)| (take_knowledge_of(user, &dict), filter))
+            .collect();
+
+        let sesh = PracticeSession::filtered(parts, &options);
+
+        if sesh.get_pool_size() == 0 {
+            for knowl in sesh.recover_knowledge() {
+                user.add_knowledge(knowl);
+            }
+            return false;
+        }
+
+        self.practice_session = Some(sesh);
+
+        true
+    }
+
+    /// Starts a drill on the attributes of the selected dictionary's active words.
+    pub fn start_drill_session(&mut self, options: DrillOptions) -> bool {
+        let dict = match &self.current_dict {
+            Some(dict) => self.dicts[&dict.uid].clone(),
+            None => return false,
+        };
+
+        self.start_session(dict, |dict, knowl| {
+            PracticeSession::drill(dict, knowl, &options)
+        })
+    }
+
+    /// Starts a session of cloze cards on the selected dictionary's active words.
+    pub fn start_cloze_session(&mut self, options: ClozeOptions) -> bool {
+        let dict = match &self.current_dict {
+            Some(dict) => self.dicts[&dict.uid].clone(),
+            None => return false,
+        };
+
+        self.start_session(dict, |dict, knowl| {
+            PracticeSession::cloze(dict, knowl, &options)
+        })
+    }
+
+    /// Starts a session that shows the linked words of `options.prompt` and asks for the words
+    /// of `options.answer`.
+    pub fn start_pair_session(&mut self, options: PairSessionOptions) -> bool {
+        let find = |id: &DictID| {
+            self.visible_dicts()
+                .find(|d| d.get_uid() == id.uid)
+                .cloned()
+        };
+
+        match (find(&options.answer), find(&options.prompt)) {
+            (Some(dict), Some(prompt)) => self.start_session(dict, |dict, knowl| {
+                PracticeSession::paired(dict, knowl, prompt)
+            }),
+            _ => false,
+        }
+    }
+
+    /// Starts a session on the due words of several dictionaries. Answers go to the knowledge
+    /// of the word's own dictionary.
+    pub fn start_mixed_session(&mut self, options: MixedSessionOptions) -> bool {
+        let user = self
+            .current_user
+            .as_ref()
+            .and_then(|u| self.users.get(&u.name));
+
+        let picked: Vec<(Arc<Dictionary>, usize)> = match &options.dicts {
+            Some(dicts) => dicts
+                .iter()
+                .filter_map(|q| {
+                    let dict = self.visible_dicts().find(|d| d.get_uid() == q.dict.uid)?;
+                    Some((dict.clone(), q.quota))
+                })
+                .collect(),
+            None => match user {
+                Some(user) => user
+                    .get_knowledge()
+                    .iter()
+                    .map(|k| (k.get_dict(), options.quota))
+                    .collect(),
+                None => return false,
+            },
+        };
+
+        let user = match self.current_user.as_ref() {
+            Some(user) => self.users.get_mut(&user.name).unwrap(),
+            None => return false,
+        };
+
+        let mut parts: Vec<(Knowledge, usize)> = Vec::new();
+        for (dict, quota) in picked {
+            if parts.iter().any(|(k, _)| Arc::ptr_eq(&k.get_dict(), &dict)) {
+                continue;
+            }
+
+            parts.push((take_knowledge_of(user, &dict), quota));
+        }
+
+        let sesh = PracticeSession::mixed(parts);
+
+        if sesh.get_pool_size() == 0 {
+            for knowl in sesh.recover_knowledge() {
+                user.add_knowledge(knowl);
+            }
+            return false;
+        }
+
+        self.practice_session = Some(sesh);
+
+        true
+    }
+
+    /// Starts a session on `dict` with the current user's knowledge of it.
+    fn start_session<F>(&mut self, dict: Arc<Dictionary>, build: F) -> bool
+    where
+        F: FnOnce(&Dictionary, Knowledge) -> PracticeSession,
+    {
+        if self.current_user.is_none() {
             return false;
         }
 
@@ -355,7 +2097,7 @@ impl Application {
             .users
             .get_mut(&self.current_user.as_ref().unwrap().name)
             .unwrap();
-        let dict = &self.dicts[&self.current_dict.as_ref().unwrap().name.clone()];
+        let dict = &dict;
 
         let knowl = {
             let mut t = std::ptr::null();
@@ -369,11 +2111,12 @@ impl Application {
                 .unwrap_or(Knowledge::create(dict.clone()))
         };
 
-        let s = knowl.get_active_words() as u32;
-        let sesh = PracticeSession::new(&dict, knowl, s);
+        let sesh = build(dict, knowl);
 
         if sesh.get_pool_size() == 0 {
-            user.add_knowledge(sesh.recover_knowledge());
+            for knowl in sesh.recover_knowledge() {
+                user.add_knowledge(knowl);
+            }
             return false;
         }
 
@@ -391,7 +2134,7 @@ impl Application {
             .users
             .get(&self.current_user.as_ref().unwrap().name)
             .unwrap();
-        let dict = &self.dicts[&self.current_dict.as_ref().unwrap().name.clone()];
+        let dict = &self.dicts[&self.current_dict.as_ref().unwrap().uid];
 
         let knowl = {
             let mut t = None;
@@ -419,7 +2162,7 @@ impl Application {
             .users
             .get_mut(&self.current_user.as_ref().unwrap().name)
             .unwrap();
-        let dict = &self.dicts[&self.current_dict.as_ref().unwrap().name.clone()];
+        let dict = &self.dicts[&self.current_dict.as_ref().unwrap().uid];
 
         let mut knowl = {
             let mut t = ptr::null();
@@ -445,22 +2188,156 @@ impl Application {
         self.current_word = Some(self.practice_session.as_mut().unwrap().pick_word());
     }
 
-    pub fn get_current_word(&self) -> Option<crate::words::for_frontend::Word> {
-        let dict = self.dicts[&self
-            .current_dict
-            .as_ref()
-            .expect("No dictionary selected!")
-            .name
-            .to_owned()]
-            .as_ref();
+    /// Grades a typed answer for the current card. Inflected forms of a word count as right,
+    /// attributes and the blanked out form of a cloze card have to match once normalized.
+    pub fn check_typed_answer(&self, answer: &str) -> bool {
+        let (dict, id, card) = match &self.current_word {
+            Some(current) => current,
+            None => return false,
+        };
+        let dict = match self.dicts.get(dict) {
+            Some(dict) => dict,
+            None => return false,
+        };
+
+        match card {
+            Card::Word => dict.accepts_answer(*id, answer),
+            Card::Attribute(name) => dict
+                .get_word_from_id(*id)
+                .attributes
+                .iter()
+                .find(|a| a.name == *name)
+                .map_or(false, |a| {
+                    dict.normalize(&a.value) == dict.normalize(answer)
+                }),
+            Card::Cloze(example) => dict.cloze(*id, *example).map_or(false, |c| {
+                dict.normalize(&c.answer) == dict.normalize(answer)
+            }),
+        }
+    }
+
+    pub fn get_current_word(&self) -> Option<CurrentWord> {
+        let (dict, id, _) = self.current_word.as_ref()?;
+        let dict = self.dicts.get(dict)?;
+        let user = self.users.get(&self.current_user.as_ref()?.name)?;
+
+        let mut word: crate::words::for_frontend::Word = dict.get_word_from_id(*id).clone().into();
+        let o = user.get_override(dict, *id);
+        if let Some(o) = o {
+            if let Some(definition) = &o.definition {
+                word.definition = definition.to_owned();
+            }
+            if let Some(pronunciation) = &o.pronunciation {
+                word.pronunciation = Some(pronunciation.to_owned());
+            }
+        }
+
+        let starred = user
+            .get_list(STARRED_LIST)
+            .map_or(false, |list| list.contains(&(dict.get_uid(), *id)));
+
+        Some(CurrentWord {
+            word,
+            note: user.get_note(dict.get_uid(), *id).map(|n| n.to_owned()),
+            overridden: o.is_some(),
+            starred,
+        })
+    }
+
+    pub fn get_current_card(&self) -> Option<Card> {
+        Some(self.current_word.as_ref()?.2.clone())
+    }
 
-        Some(dict.get_word_from_id(self.current_word?).clone().into())
+    /// The blanked out sentence of the current card, if it is a cloze card.
+    pub fn get_current_cloze(&self) -> Option<Cloze> {
+        match self.current_word.as_ref()? {
+            (dict, id, Card::Cloze(example)) => self.dicts.get(dict)?.cloze(*id, *example),
+            _ => None,
+        }
     }
 
     pub fn practice_current_word(&mut self, result: bool) {
         let sesh = self.practice_session.as_mut().unwrap();
+        let (dict, id, card) = self.current_word.clone().unwrap();
+
+        sesh.practice(dict, id, &card, result);
+
+        // drilling a form doesn't give the linked words away
+        if !sesh.cram && card == Card::Word {
+            self.bury_linked(dict, id);
+        }
+    }
+
+    /// Buries the words linked to a practiced one until tomorrow, so they don't give each
+    /// other away.
+    fn bury_linked(&mut self, dict: DictUID, id: WordID) {
+        let dict = match self.dicts.get(&dict) {
+            Some(dict) => dict.clone(),
+            None => return,
+        };
+
+        let until = next_midnight();
+
+        for (other, linked) in self.linked_words(&dict, id) {
+            if let Some(sesh) = &mut self.practice_session {
+                sesh.remove_word(other.get_uid(), linked);
+            }
+
+            // users who never practiced a dictionary have nothing to bury
+            if self.find_knowledge(&other).is_some() {
+                if let Ok(knowl) = self.find_knowledge_mut(&other) {
+                    knowl.bury(linked, until);
+                }
+            }
+        }
+    }
 
-        sesh.knowledge.practice(self.current_word.unwrap(), result);
+    /// Words of every visible dictionary that share the concept of `id`, except itself.
+    fn linked_words(&self, dict: &Dictionary, id: WordID) -> Vec<(Arc<Dictionary>, WordID)> {
+        let concept = match &dict.get_word_from_id(id).concept {
+            Some(concept) => concept,
+            None => return Vec::new(),
+        };
+
+        let mut out = Vec::new();
+        for other in self.visible_dicts() {
+            for linked in other.find_concept(concept) {
+                if other.get_uid() != dict.get_uid() || *linked != id {
+                    out.push((other.clone(), *linked));
+                }
+            }
+        }
+
+        out
+    }
+
+    pub fn get_linked_words(&self, dict: DictID, id: WordID) -> Result<Box<[LinkedWord]>, Error> {
+        let dict = self.get_dict(&dict)?;
+        if !dict.contains_id(id) {
+            return Err("Word not found!")?;
+        }
+
+        Ok(self
+            .linked_words(&dict, id)
+            .into_iter()
+            .map(|(other, linked)| LinkedWord::of(&other, linked))
+            .collect())
+    }
+
+    /// The shown words of a pair session, which are linked to the current word.
+    pub fn get_current_prompt(&self) -> Option<Box<[LinkedWord]>> {
+        let prompt = self.practice_session.as_ref()?.prompt.as_ref()?;
+        let (dict, id, _) = self.current_word.as_ref()?;
+        let word = self.dicts.get(dict)?.get_word_from_id(*id);
+        let concept = word.concept.as_ref()?;
+
+        Some(
+            prompt
+                .find_concept(concept)
+                .iter()
+                .map(|linked| LinkedWord::of(prompt, *linked))
+                .collect(),
+        )
     }
 
     pub fn get_session_len(&self) -> usize {
@@ -469,13 +2346,16 @@ impl Application {
 
     pub fn conclude_session(&mut self) {
         let kw = self.practice_session.take().unwrap().recover_knowledge();
+        self.current_word = None;
 
         let user = self
             .users
             .get_mut(&self.current_user.as_ref().unwrap().name)
             .unwrap();
 
-        user.add_knowledge(kw);
+        for knowl in kw {
+            user.add_knowledge(knowl);
+        }
     }
 
     pub fn get_users(&self) -> Box<[UserID]> {
@@ -511,19 +2391,36 @@ impl Application {
     pub fn save_current_user(&mut self) -> Result<(), Error> {
         let user = self
             .users
-            .get_mut(&self.current_user.as_ref().unwrap().name)
+            .get(&self.current_user.as_ref().unwrap().name)
             .unwrap();
 
+        self.save_user(user)
+    }
+
+    fn user_path(&self, name: &str) -> PathBuf {
         let mut user_path = PathBuf::new();
         user_path.push(&self.user_dir);
-        user_path.push(user.get_name().to_owned() + ".usr");
-        let mut user_file = File::create(user_path)?;
+        user_path.push(name.to_owned() + ".usr");
+
+        user_path
+    }
+
+    fn save_user(&self, user: &User) -> Result<(), Error> {
+        let mut user_file = File::create(self.user_path(user.get_name()))?;
 
         user.save_to(&mut user_file)?;
 
         Ok(())
     }
 
+    fn save_all_users(&self) -> Result<(), Error> {
+        for user in self.users.values() {
+            self.save_user(user)?;
+        }
+
+        Ok(())
+    }
+
     pub fn get_current_user(&self) -> Option<UserID> {
         self.current_user.clone()
     }
@@ -563,6 +2460,15 @@ impl Application {
         self.dict_dir.clone()
     }
 
+    /// Where importing the dictionary file at `path` installs it.
+    pub fn installed_path(&self, path: &Path) -> PathBuf {
+        let mut to = self.get_dict_dir();
+        to.push(path.file_stem().unwrap_or_default());
+        to.set_extension("dct");
+
+        to
+    }
+
     pub fn get_user_id(&self, name: String) -> Option<UserID> {
         if self.users.contains_key(&name) {
             Some(UserID { name })
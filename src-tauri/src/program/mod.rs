@@ -1,9 +1,11 @@
 mod app;
 mod loader;
+mod trash;
 mod user;
 
 pub mod filemanager;
 
 pub use app::*;
 pub use loader::*;
-pub use user::*;
+pub use trash::TrashEntry;
+pub use user::WordOverride;
@@ -0,0 +1,164 @@
+use std::{
+    cmp::Reverse,
+    fs::{create_dir, read_dir, remove_dir_all, rename},
+    path::{Path, PathBuf},
+};
+
+use chrono::Utc;
+use serde::Serialize;
+
+use crate::error::Error;
+
+#[derive(Serialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum TrashKind {
+    Dict,
+    User,
+}
+
+impl TrashKind {
+    fn as_str(self) -> &'static str {
+        match self {
+            TrashKind::Dict => "dict",
+            TrashKind::User => "user",
+        }
+    }
+
+    fn parse(s: &str) -> Option<TrashKind> {
+        match s {
+            "dict" => Some(TrashKind::Dict),
+            "user" => Some(TrashKind::User),
+            _ => None,
+        }
+    }
+}
+
+/// A deleted dictionary or user that can still be restored.
+#[derive(Serialize, Clone, Debug)]
+pub struct TrashEntry {
+    /// Name of the entry's folder, used to restore it
+    pub id: String,
+    pub kind: TrashKind,
+    /// File name of the dictionary or name of the user
+    pub name: String,
+    /// Unix seconds
+    pub deleted: i64,
+}
+
+impl TrashEntry {
+    fn parse(id: &str) -> Option<TrashEntry> {
+        let mut parts = id.splitn(3, '-');
+
+        let deleted = parts.next()?.parse().ok()?;
+        let kind = TrashKind::parse(parts.next()?)?;
+        let name = parts.next()?.to_owned();
+
+        Some(TrashEntry {
+            id: id.to_owned(),
+            kind,
+            name,
+            deleted,
+        })
+    }
+}
+
+/// Folder that deleted files are moved into. Every deletion gets its own subfolder named
+/// `<time>-<kind>-<name>`.
+pub struct Trash {
+    dir: PathBuf,
+}
+
+impl Trash {
+    pub fn new(dir: PathBuf) -> Trash {
+        Trash { dir }
+    }
+
+    /// Creates the folder for a new entry.
+    pub fn create_entry(
+        &self,
+        kind: TrashKind,
+        name: &str,
+    ) -> Result<(TrashEntry, PathBuf), Error> {
+        let deleted = Utc::now().timestamp();
+        let mut id = format!("{}-{}-{}", deleted, kind.as_str(), name);
+
+        let mut path = self.dir.join(&id);
+        let mut n = 1;
+        while path.exists() {
+            n += 1;
+            id = format!("{}-{}-{} ({})", deleted, kind.as_str(), name, n);
+            path = self.dir.join(&id);
+        }
+
+        create_dir(&path)?;
+
+        let entry = TrashEntry {
+            id,
+            kind,
+            name: name.to_owned(),
+            deleted,
+        };
+
+        Ok((entry, path))
+    }
+
+    pub fn get_entries(&self) -> Result<Box<[TrashEntry]>, Error> {
+        let mut out = Vec::new();
+
+        for r in read_dir(&self.dir)? {
+            let path = r?.path();
+            if !path.is_dir() {
+                continue;
+            }
+
+            if let Some(entry) = path
+                .file_name()
+                .and_then(|n| n.to_str())
+                .and_then(TrashEntry::parse)
+            {
+                out.push(entry);
+            }
+        }
+
+        out.sort_by_key(|e| Reverse(e.deleted));
+
+        Ok(out.into_boxed_slice())
+    }
+
+    /// Looks up an entry and its folder. Only names of existing entries are accepted.
+    pub fn get_entry(&self, id: &str) -> Result<(TrashEntry, PathBuf), Error> {
+        let entry = TrashEntry::parse(id).ok_or("Invalid trash entry!")?;
+        let path = self.dir.join(&entry.id);
+
+        if path.parent() != Some(self.dir.as_path()) || !path.is_dir() {
+            return Err("Trash entry not found!")?;
+        }
+
+        Ok((entry, path))
+    }
+
+    pub fn remove_entry(&self, id: &str) -> Result<(), Error> {
+        let (_, path) = self.get_entry(id)?;
+
+        remove_dir_all(path)?;
+
+        Ok(())
+    }
+
+    /// Permanently deletes everything in the trash.
+    pub fn empty(&self) -> Result<(), Error> {
+        for entry in self.get_entries()?.iter() {
+            self.remove_entry(&entry.id)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Moves `file` into `folder`, keeping its file name.
+pub fn move_into(file: &Path, folder: &Path) -> Result<PathBuf, Error> {
+    let to = folder.join(file.file_name().ok_or("Not a file!")?);
+    rename(file, &to)?;
+
+    Ok(to)
+}
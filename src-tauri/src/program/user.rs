@@ -1,18 +1,29 @@
 use std::{
+    collections::{BTreeMap, HashMap},
     io::{Read, Write},
     mem::size_of,
 };
 
+use serde::{Deserialize, Serialize};
 use struct_version_manager::version_macro::version_mod;
 
 use crate::{
     error::Error,
     tools::{dict_map::DictMap, u8_buffer::U8Buffer},
-    words::Knowledge,
+    words::{DictUID, Dictionary, Knowledge, WordID},
 };
 
 const USER_HEADER: &'static str = "USER_FILE";
-const USER_VERSION: &'static str = "0.1";
+const USER_VERSION: &'static str = "0.2";
+
+/// Word list every user has, for words starred while reviewing or browsing.
+pub const STARRED_LIST: &str = "Starred";
+
+/// Saved knowledge of a dictionary that isn't loaded.
+type ArchivedKnowledge = Box<[u8]>;
+
+/// Words of a word list, by dictionary and id.
+type WordList = Vec<(DictUID, WordID)>;
 
 #[version_mod(UserData)]
 mod user_data {
@@ -21,39 +32,128 @@ mod user_data {
         use serde::{Deserialize, Serialize};
         use struct_version_manager::version_macro::version;
 
-        use crate::{
-            error::Error,
-            program::{user::encode_knowledge_data, User},
-        };
-
         #[derive(Serialize, Deserialize)]
         #[version("0.1")]
         pub struct UserData {
             pub name: String,
             pub knowledge_data: Box<[u8]>,
         }
+    }
 
-        impl UserData {
-            pub fn create(user: &User) -> Result<UserData, Error> {
-                let name = user.name.to_owned();
-                let knowledge_data = encode_knowledge_data(&user.knowledge)?;
+    pub mod v2 {
+        use serde::{Deserialize, Serialize};
+        use struct_version_manager::version_macro::version;
 
-                Ok(UserData {
-                    name,
-                    knowledge_data,
-                })
+        use super::super::{WordList, WordOverride};
+        use crate::words::{DictUID, WordID};
+
+        /// Adds notes, overrides and word lists.
+        #[derive(Serialize, Deserialize)]
+        #[version("0.2")]
+        pub struct UserData {
+            pub name: String,
+            pub knowledge_data: Box<[u8]>,
+            pub notes: Box<[(DictUID, WordID, String)]>,
+            pub overrides: Box<[(DictUID, WordID, WordOverride)]>,
+            pub lists: Box<[(String, WordList)]>,
+        }
+
+        impl From<super::v1::UserData> for UserData {
+            fn from(value: super::v1::UserData) -> Self {
+                UserData {
+                    name: value.name,
+                    knowledge_data: value.knowledge_data,
+                    notes: Box::new([]),
+                    overrides: Box::new([]),
+                    lists: Box::new([]),
+                }
             }
         }
     }
 }
 
-use user_data::v1::UserData;
+use user_data::v2::UserData;
+
+/// A user's own definition or pronunciation of a word, shown instead of the dictionary's.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WordOverride {
+    /// Headword of the word when it was overridden, the override only applies to words with it
+    pub headword: String,
+    pub definition: Option<String>,
+    pub pronunciation: Option<String>,
+}
+
+impl UserData {
+    fn create(user: &User) -> Result<UserData, Error> {
+        let name = user.name.to_owned();
+        let knowledge_data = encode_knowledge_data(&user.knowledge, &user.archived)?;
+        let notes = user
+            .notes
+            .iter()
+            .map(|((dict, id), note)| (*dict, *id, note.to_owned()))
+            .collect();
+        let overrides = user
+            .overrides
+            .iter()
+            .map(|((dict, id), o)| (*dict, *id, o.clone()))
+            .collect();
+        let lists = user
+            .lists
+            .iter()
+            .map(|(name, words)| (name.to_owned(), words.clone()))
+            .collect();
+
+        Ok(UserData {
+            name,
+            knowledge_data,
+            notes,
+            overrides,
+            lists,
+        })
+    }
+
+    fn size_of(&self) -> usize {
+        size_of::<UserData>()
+            + size_of::<u8>() * self.knowledge_data.len()
+            + self
+                .notes
+                .iter()
+                .map(|(_, _, note)| size_of::<(DictUID, WordID, usize)>() + note.len())
+                .sum::<usize>()
+            + self
+                .overrides
+                .iter()
+                .map(|(_, _, o)| {
+                    size_of::<(DictUID, WordID, WordOverride)>()
+                        + o.headword.len()
+                        + o.definition.as_ref().map_or(0, |d| d.len())
+                        + o.pronunciation.as_ref().map_or(0, |p| p.len())
+                })
+                .sum::<usize>()
+            + self
+                .lists
+                .iter()
+                .map(|(name, words)| {
+                    size_of::<(usize, usize)>()
+                        + name.len()
+                        + words.len() * size_of::<(DictUID, WordID)>()
+                })
+                .sum::<usize>()
+    }
+}
 
 use super::filemanager;
 
 pub struct User {
     name: String,
     knowledge: Vec<Knowledge>,
+    /// Knowledge of dictionaries that aren't loaded, kept as is until they come back
+    archived: Vec<ArchivedKnowledge>,
+    /// Mnemonics and such, kept apart from the dictionaries so every user has their own
+    notes: HashMap<(DictUID, WordID), String>,
+    overrides: HashMap<(DictUID, WordID), WordOverride>,
+    /// Named lists of words, in the order they were added
+    lists: BTreeMap<String, WordList>,
 }
 
 impl User {
@@ -61,6 +161,10 @@ impl User {
         User {
             name,
             knowledge: Vec::new(),
+            archived: Vec::new(),
+            notes: HashMap::new(),
+            overrides: HashMap::new(),
+            lists: BTreeMap::new(),
         }
     }
 
@@ -80,18 +184,141 @@ impl User {
         None
     }
 
+    /// Loads the archived knowledge of the dictionaries in `container`.
+    pub fn reattach_knowledge(&mut self, container: &DictMap) -> Result<(), Error> {
+        let mut archived = Vec::new();
+
+        for data in std::mem::take(&mut self.archived) {
+            match Knowledge::load_from(&mut &data[..], container)? {
+                Some(k) => self.knowledge.push(k),
+                None => archived.push(data),
+            }
+        }
+
+        self.archived = archived;
+        Ok(())
+    }
+
     pub fn get_knowledge(&self) -> &[Knowledge] {
         &self.knowledge
     }
 
+    pub fn get_knowledge_mut(&mut self) -> &mut [Knowledge] {
+        &mut self.knowledge
+    }
+
+    pub fn get_note(&self, dict: DictUID, word: WordID) -> Option<&str> {
+        self.notes.get(&(dict, word)).map(|n| n.as_str())
+    }
+
+    /// Sets the note on a word, an empty note removes it.
+    pub fn set_note(&mut self, dict: DictUID, word: WordID, note: String) {
+        if note.trim().is_empty() {
+            self.notes.remove(&(dict, word));
+        } else {
+            self.notes.insert((dict, word), note);
+        }
+    }
+
+    /// The override of a word of `dict`. Words that got a new id in a dictionary update keep
+    /// their override as long as their headword stays the same.
+    pub fn get_override(&self, dict: &Dictionary, word: WordID) -> Option<&WordOverride> {
+        let uid = dict.get_uid();
+        let headword = &dict.get_word_from_id(word).text;
+
+        if let Some(o) = self.overrides.get(&(uid, word)) {
+            if o.headword == *headword {
+                return Some(o);
+            }
+        }
+
+        self.overrides
+            .iter()
+            .find(|((d, id), o)| *d == uid && o.headword == *headword && is_orphaned(dict, *id, o))
+            .map(|(_, o)| o)
+    }
+
+    /// Sets the override of a word, `None` removes it. Replaces an override the word kept
+    /// through an update.
+    pub fn set_override(&mut self, dict: &Dictionary, word: WordID, o: Option<WordOverride>) {
+        let uid = dict.get_uid();
+        let headword = &dict.get_word_from_id(word).text;
+
+        self.overrides.retain(|(d, id), o| {
+            *d != uid || o.headword != *headword || !is_orphaned(dict, *id, o)
+        });
+
+        if let Some(o) = o {
+            self.overrides.insert((uid, word), o);
+        } else {
+            self.overrides.remove(&(uid, word));
+        }
+    }
+
+    /// Names of the user's word lists, sorted.
+    pub fn get_lists(&self) -> impl Iterator<Item = &str> {
+        self.lists.keys().map(|n| n.as_str())
+    }
+
+    pub fn get_list(&self, name: &str) -> Option<&[(DictUID, WordID)]> {
+        self.lists.get(name).map(|l| l.as_slice())
+    }
+
+    /// Creates an empty list, returns `false` if there is one with this name already.
+    pub fn create_list(&mut self, name: String) -> bool {
+        if self.lists.contains_key(&name) {
+            return false;
+        }
+
+        self.lists.insert(name, Vec::new());
+        true
+    }
+
+    pub fn delete_list(&mut self, name: &str) -> bool {
+        self.lists.remove(name).is_some()
+    }
+
+    /// Adds a word to a list, returns `false` if there is no such list. The starred list is
+    /// created when needed.
+    pub fn add_to_list(&mut self, name: &str, dict: DictUID, word: WordID) -> bool {
+        if name == STARRED_LIST {
+            self.lists.entry(name.to_owned()).or_default();
+        }
+
+        match self.lists.get_mut(name) {
+            Some(list) => {
+                if !list.contains(&(dict, word)) {
+                    list.push((dict, word));
+                }
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Takes a word off a list, returns `false` if there is no such list.
+    pub fn remove_from_list(&mut self, name: &str, dict: DictUID, word: WordID) -> bool {
+        match self.lists.get_mut(name) {
+            Some(list) => {
+                list.retain(|w| *w != (dict, word));
+                true
+            }
+            None => false,
+        }
+    }
+
     pub fn get_name<'u>(&'u self) -> &'u str {
         &self.name
     }
 
+    pub fn set_name(&mut self, name: String) {
+        self.name = name;
+    }
+
     pub fn save_to<T: Write>(&self, writable: &mut T) -> Result<usize, Error> {
         let data = UserData::create(&self)?;
 
-        let size_estimate = size_of::<UserData>() + size_of::<u8>() * data.knowledge_data.len();
+        let size_estimate = data.size_of();
 
         let mut alloc = vec![0u8; size_estimate];
 
@@ -107,48 +334,68 @@ impl User {
     }
 
     pub fn load_from<T: Read>(readable: &mut T, dict_container: &DictMap) -> Result<Self, Error> {
-        let mut file = filemanager::read_file(readable)?;
+        let file = filemanager::read_file(readable)?;
 
         if file.header != USER_HEADER {
             return Err("Invalid File Header!")?;
         }
 
-        match file.version.as_str() {
-            USER_VERSION => {
-                let mut data = postcard::from_bytes::<UserData>(&mut file.data)?;
-
-                let kw_data = decode_knowledge_data(&mut data.knowledge_data, dict_container)?;
-
-                Ok(User {
-                    name: data.name,
-                    knowledge: kw_data.into_vec(),
-                })
-            }
+        let mut data: UserData = match file.version.as_str() {
+            USER_VERSION => postcard::from_bytes(&file.data)?,
+            "0.1" => postcard::from_bytes::<user_data::v1::UserData>(&file.data)?.into(),
             v => {
-                let knowl = match v {
-                    _ => {
-                        println!("{}", v);
-                        Err("Unknown File Version!")?
-                    }
-                };
-
-                Ok(knowl)
+                println!("{}", v);
+                return Err("Unknown File Version!")?;
             }
-        }
+        };
+
+        let (knowledge, archived) =
+            decode_knowledge_data(&mut data.knowledge_data, dict_container)?;
+
+        Ok(User {
+            name: data.name,
+            knowledge,
+            archived,
+            notes: data
+                .notes
+                .into_vec()
+                .into_iter()
+                .map(|(dict, id, note)| ((dict, id), note))
+                .collect(),
+            overrides: data
+                .overrides
+                .into_vec()
+                .into_iter()
+                .map(|(dict, id, o)| ((dict, id), o))
+                .collect(),
+            lists: data.lists.into_vec().into_iter().collect(),
+        })
     }
 }
 
-fn encode_knowledge_data(knowledge: &[Knowledge]) -> Result<Box<[u8]>, Error> {
+/// Whether the word an override was made for is gone from `dict` or has a different headword now.
+fn is_orphaned(dict: &Dictionary, id: WordID, o: &WordOverride) -> bool {
+    !dict.contains_id(id) || dict.get_word_from_id(id).text != o.headword
+}
+
+fn encode_knowledge_data(
+    knowledge: &[Knowledge],
+    archived: &[ArchivedKnowledge],
+) -> Result<Box<[u8]>, Error> {
     let mut size_estimate = size_of::<usize>();
 
     for kw in knowledge {
         size_estimate += kw.estimate_serialized_size();
     }
+    for kw in archived {
+        size_estimate += size_of::<u64>() + kw.len();
+    }
 
     let mut data = vec![0u8; size_estimate];
     println!("Estimated size: {}", size_estimate);
 
-    let size_len = postcard::to_slice(&knowledge.len(), &mut data[..])?.len();
+    let count = knowledge.len() + archived.len();
+    let size_len = postcard::to_slice(&count, &mut data[..])?.len();
 
     let mut buf = U8Buffer::create_empty(&mut data[size_len..]);
     for k in knowledge {
@@ -160,6 +407,10 @@ fn encode_knowledge_data(knowledge: &[Knowledge]) -> Result<Box<[u8]>, Error> {
         buf.write(&size_bytes)?;
         buf.advance_write(kw_size);
     }
+    for kw in archived {
+        buf.write_all(&(kw.len() as u64).to_ne_bytes())?;
+        buf.write_all(kw)?;
+    }
 
     let total_size = buf.len() + size_len;
     drop(buf);
@@ -170,7 +421,11 @@ fn encode_knowledge_data(knowledge: &[Knowledge]) -> Result<Box<[u8]>, Error> {
     Ok(data.into_boxed_slice())
 }
 
-fn decode_knowledge_data(data: &mut [u8], container: &DictMap) -> Result<Box<[Knowledge]>, Error> {
+/// The knowledge in `data`, and the saved knowledge of dictionaries missing from `container`.
+fn decode_knowledge_data(
+    data: &mut [u8],
+    container: &DictMap,
+) -> Result<(Vec<Knowledge>, Vec<ArchivedKnowledge>), Error> {
     let (count, used) = {
         let t = postcard::take_from_bytes::<usize>(data)?;
         (t.0, data.len() - t.1.len())
@@ -179,6 +434,7 @@ fn decode_knowledge_data(data: &mut [u8], container: &DictMap) -> Result<Box<[Kn
 
     let mut out = Vec::new();
     out.reserve(count);
+    let mut archived = Vec::new();
     let mut buf = U8Buffer::create_full(data);
     for _ in 0..count {
         let mut size_bytes = [0u8; size_of::<u64>()];
@@ -186,12 +442,16 @@ fn decode_knowledge_data(data: &mut [u8], container: &DictMap) -> Result<Box<[Kn
         let kw_size = u64::from_ne_bytes(size_bytes) as usize;
 
         let og_len = buf.force_len(kw_size);
+        let raw: ArchivedKnowledge = buf.get_data().into();
         let k = Knowledge::load_from(&mut buf, container)?;
         buf.force_len(og_len);
 
         buf.advance_read(kw_size);
-        out.push(k);
+        match k {
+            Some(k) => out.push(k),
+            None => archived.push(raw),
+        }
     }
 
-    Ok(out.into_boxed_slice())
+    Ok((out, archived))
 }
@@ -0,0 +1,79 @@
+use std::{collections::HashMap, fs::read_dir, path::Path};
+
+use crate::{
+    error::Error,
+    words::{Dictionary, WordID},
+};
+
+use super::normalize::lowercase;
+
+/// Splits text into lowercase word tokens. Apostrophes stay part of the word.
+///
+/// `language` is the ISO 639 code of the text, used for language specific case folding.
+pub fn tokenize<'a>(text: &'a str, language: Option<&'a str>) -> impl Iterator<Item = String> + 'a {
+    text.split(|c: char| !(c.is_alphanumeric() || c == '\''))
+        .map(|t| t.trim_matches('\''))
+        .filter(|t| !t.is_empty())
+        .map(move |t| lowercase(t, language))
+}
+
+/// Counts how often each word of `dict` occurs in the `.txt` files of `folder`.
+///
+/// Multi-word headwords are matched as whole phrases, inflected forms through the lemma table.
+/// Homographs share the same count.
+pub fn count_headwords<P: AsRef<Path>>(folder: P, dict: &Dictionary) -> Result<Box<[u64]>, Error> {
+    let ids = dict.get_word_ids();
+    let language = dict.get_metadata().source_language.as_deref();
+
+    // normalized headword -> indices into `ids`
+    let mut headwords: HashMap<String, Vec<usize>> = HashMap::new();
+    let mut max_len = 1;
+    for (i, id) in ids.iter().enumerate() {
+        let tokens: Vec<String> = tokenize(&dict.get_word_from_id(*id).text, language).collect();
+        if tokens.is_empty() {
+            continue;
+        }
+
+        max_len = max_len.max(tokens.len());
+        headwords.entry(tokens.join(" ")).or_default().push(i);
+    }
+
+    let positions: HashMap<WordID, usize> =
+        ids.iter().enumerate().map(|(i, id)| (*id, i)).collect();
+
+    let mut counts = vec![0u64; ids.len()];
+
+    for entry in read_dir(folder)? {
+        let path = entry?.path();
+
+        match path.extension().and_then(|e| e.to_str()) {
+            Some("txt") => (),
+            _ => continue,
+        }
+
+        let text = std::fs::read_to_string(&path)?;
+        let tokens: Vec<String> = tokenize(&text, language).collect();
+
+        for start in 0..tokens.len() {
+            let end = (start + max_len).min(tokens.len());
+            for len in 1..=(end - start) {
+                let phrase = tokens[start..start + len].join(" ");
+
+                if let Some(indices) = headwords.get(&phrase) {
+                    for i in indices {
+                        counts[*i] += 1;
+                    }
+                } else {
+                    // inflected forms count for their headword
+                    for id in dict.find_lemma(&phrase) {
+                        if let Some(i) = positions.get(id) {
+                            counts[*i] += 1;
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(counts.into_boxed_slice())
+}
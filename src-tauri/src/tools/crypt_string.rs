@@ -2,6 +2,9 @@ use serde::{de::Visitor, Deserialize, Serialize};
 
 fn encrypt_string(string: String) -> Box<[u8]> {
     let mut bytes = string.into_bytes().into_boxed_slice();
+    if bytes.is_empty() {
+        return bytes;
+    }
 
     let mut overflow = 0u8;
     for c in bytes.iter_mut().rev() {
@@ -19,6 +22,10 @@ fn encrypt_string(string: String) -> Box<[u8]> {
 }
 
 fn decrypt_string(mut string: Box<[u8]>) -> String {
+    if string.is_empty() {
+        return String::new();
+    }
+
     let mut overflow = 0u8;
     for c in string.iter_mut() {
         let _c = !(*c);
@@ -7,10 +7,10 @@ use std::{
     sync::Arc,
 };
 
-use crate::words::Dictionary;
+use crate::words::{DictUID, Dictionary};
 
 pub struct DictMap {
-    map: HashMap<String, Arc<Dictionary>>,
+    map: HashMap<DictUID, Arc<Dictionary>>,
 }
 
 impl DictMap {
@@ -21,20 +21,34 @@ impl DictMap {
     }
 
     pub fn insert(&mut self, dict: Arc<Dictionary>) -> Option<Arc<Dictionary>> {
-        self.map.insert(dict.get_title().to_owned(), dict)
+        self.map.insert(dict.get_uid(), dict)
     }
 
-    pub fn values<'a>(&'a self) -> Values<'a, String, Arc<Dictionary>> {
+    pub fn remove(&mut self, uid: &DictUID) -> Option<Arc<Dictionary>> {
+        self.map.remove(uid)
+    }
+
+    pub fn values<'a>(&'a self) -> Values<'a, DictUID, Arc<Dictionary>> {
         self.map.values()
     }
 
-    pub fn keys<'a>(&'a self) -> Keys<'a, String, Arc<Dictionary>> {
+    pub fn keys<'a>(&'a self) -> Keys<'a, DictUID, Arc<Dictionary>> {
         self.map.keys()
     }
 
-    pub fn get(&self, index: &String) -> Option<&Arc<Dictionary>> {
+    pub fn get(&self, index: &DictUID) -> Option<&Arc<Dictionary>> {
         self.map.get(index)
     }
+
+    pub fn contains(&self, uid: &DictUID) -> bool {
+        self.map.contains_key(uid)
+    }
+
+    /// Finds a dictionary by title. Titles are not unique, so this is only meant for files
+    /// from before dictionaries had ids.
+    pub fn find_by_title(&self, title: &str) -> Option<&Arc<Dictionary>> {
+        self.map.values().find(|d| d.get_title() == title)
+    }
 }
 
 impl Default for DictMap {
@@ -43,25 +57,17 @@ impl Default for DictMap {
     }
 }
 
-impl Index<&String> for DictMap {
+impl Index<&DictUID> for DictMap {
     type Output = Arc<Dictionary>;
 
-    fn index(&self, index: &String) -> &Self::Output {
+    fn index(&self, index: &DictUID) -> &Self::Output {
         self.map.index(index)
     }
 }
 
-impl<'a> Index<&'a str> for DictMap {
-    type Output = Arc<Dictionary>;
-
-    fn index(&self, index: &'a str) -> &Self::Output {
-        self.map.index(&index.to_owned())
-    }
-}
-
 impl IntoIterator for DictMap {
-    type IntoIter = <HashMap<String, Arc<Dictionary>> as IntoIterator>::IntoIter;
-    type Item = <HashMap<String, Arc<Dictionary>> as IntoIterator>::Item;
+    type IntoIter = <HashMap<DictUID, Arc<Dictionary>> as IntoIterator>::IntoIter;
+    type Item = <HashMap<DictUID, Arc<Dictionary>> as IntoIterator>::Item;
 
     fn into_iter(self) -> Self::IntoIter {
         self.map.into_iter()
@@ -69,8 +75,8 @@ impl IntoIterator for DictMap {
 }
 
 impl<'a> IntoIterator for &'a DictMap {
-    type IntoIter = <&'a HashMap<String, Arc<Dictionary>> as IntoIterator>::IntoIter;
-    type Item = <&'a HashMap<String, Arc<Dictionary>> as IntoIterator>::Item;
+    type IntoIter = <&'a HashMap<DictUID, Arc<Dictionary>> as IntoIterator>::IntoIter;
+    type Item = <&'a HashMap<DictUID, Arc<Dictionary>> as IntoIterator>::Item;
 
     fn into_iter(self) -> Self::IntoIter {
         self.map.iter()
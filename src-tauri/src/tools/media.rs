@@ -0,0 +1,116 @@
+use std::{
+    fs::{copy, create_dir_all, read_dir},
+    path::{Path, PathBuf},
+};
+
+use crate::{error::Error, words::Dictionary};
+
+/// Folder next to a dictionary file holding its audio clips and images, e.g. `french.media` for
+/// `french.dct`.
+pub fn media_dir(dict_file: &Path) -> PathBuf {
+    dict_file.with_extension("media")
+}
+
+/// Media are referenced by plain file names, so they can never point outside the media folder.
+pub fn is_valid_name(name: &str) -> bool {
+    !name.is_empty() && !name.starts_with('.') && !name.contains(|c| matches!(c, '/' | '\\' | ':'))
+}
+
+/// Copies every file `dict` refers to from `from` into `to`.
+pub fn copy_media(dict: &Dictionary, from: &Path, to: &Path) -> Result<(), Error> {
+    copy_files(&find_media(dict, from)?, from, to)
+}
+
+/// Names of the files `dict` refers to, after checking they are all in `from`.
+pub fn find_media(dict: &Dictionary, from: &Path) -> Result<Vec<String>, Error> {
+    let mut names = Vec::new();
+
+    for name in dict.get_media().iter() {
+        if !is_valid_name(name) {
+            Err(format!("Invalid media file name: {}", name))?;
+        }
+
+        if !from.join(name).is_file() {
+            Err(format!("Missing media file: {}", name))?;
+        }
+
+        names.push(name.to_string());
+    }
+
+    Ok(names)
+}
+
+/// Copies the files `names` from `from` into `to`.
+pub fn copy_files(names: &[String], from: &Path, to: &Path) -> Result<(), Error> {
+    if names.is_empty() {
+        return Ok(());
+    }
+
+    create_dir_all(to)?;
+
+    for name in names {
+        copy(from.join(name), to.join(name))?;
+    }
+
+    Ok(())
+}
+
+/// Copies the files of a media folder, if there is one.
+pub fn copy_dir(from: &Path, to: &Path) -> Result<(), Error> {
+    if !from.is_dir() {
+        return Ok(());
+    }
+
+    create_dir_all(to)?;
+
+    for r in read_dir(from)? {
+        let path = r?.path();
+
+        if path.is_file() {
+            copy(&path, to.join(path.file_name().unwrap()))?;
+        }
+    }
+
+    Ok(())
+}
+
+pub fn mime_type(path: &Path) -> &'static str {
+    let extension = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.to_lowercase());
+
+    match extension.as_deref() {
+        Some("mp3") => "audio/mpeg",
+        Some("ogg") | Some("oga") | Some("opus") => "audio/ogg",
+        Some("wav") => "audio/wav",
+        Some("m4a") => "audio/mp4",
+        Some("flac") => "audio/flac",
+        Some("png") => "image/png",
+        Some("jpg") | Some("jpeg") => "image/jpeg",
+        Some("gif") => "image/gif",
+        Some("webp") => "image/webp",
+        Some("svg") => "image/svg+xml",
+        _ => "application/octet-stream",
+    }
+}
+
+/// Decodes `%XX` escapes of a url path segment.
+pub fn percent_decode(s: &str) -> Option<String> {
+    let bytes = s.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            let hex = s.get(i + 1..i + 3)?;
+            out.push(u8::from_str_radix(hex, 16).ok()?);
+            i += 3;
+        } else {
+            out.push(bytes[i]);
+            i += 1;
+        }
+    }
+
+    String::from_utf8(out).ok()
+}
@@ -1,5 +1,8 @@
+pub mod corpus;
 pub mod crypt_string;
 pub mod dict_map;
+pub mod media;
+pub mod normalize;
 pub mod u8_buffer;
 pub mod weighted_list;
 pub mod xml;
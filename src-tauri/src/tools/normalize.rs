@@ -0,0 +1,64 @@
+use unicode_normalization::{char::is_combining_mark, UnicodeNormalization};
+
+/// Normalizes text for comparison: trims it, collapses whitespace and lowercases it with the
+/// rules of `language` (an ISO 639 code) where they differ from the default.
+pub fn normalize(text: &str, language: Option<&str>) -> String {
+    let words: Vec<&str> = text.split_whitespace().collect();
+
+    lowercase(&words.join(" "), language)
+}
+
+pub fn lowercase(text: &str, language: Option<&str>) -> String {
+    let language = language.map(|l| {
+        l.split(|c| c == '-' || c == '_')
+            .next()
+            .unwrap_or(l)
+            .to_lowercase()
+    });
+
+    match language.as_deref() {
+        // Turkic languages have a dotted and a dotless i
+        Some("tr") | Some("az") => text
+            .chars()
+            .map(|c| match c {
+                'I' => 'ı',
+                'İ' => 'i',
+                c => c,
+            })
+            .collect::<String>()
+            .to_lowercase(),
+        _ => text.to_lowercase(),
+    }
+}
+
+/// Like `normalize`, but also strips accents so `resume` matches `résumé`. Letters are
+/// decomposed (NFD) and the combining marks dropped, so accented letters of any script fold,
+/// not only Latin ones.
+pub fn fold(text: &str, language: Option<&str>) -> String {
+    let mut out = String::with_capacity(text.len());
+
+    for c in normalize(text, language).nfd() {
+        match c {
+            c if is_combining_mark(c) => (),
+            'ß' => out.push_str("ss"),
+            'æ' => out.push_str("ae"),
+            'œ' => out.push_str("oe"),
+            c => out.push(strip_stroke(c)),
+        }
+    }
+
+    out
+}
+
+/// Letters whose mark is part of the letter itself, so decomposing them leaves it on.
+fn strip_stroke(c: char) -> char {
+    match c {
+        'đ' => 'd',
+        'ħ' => 'h',
+        'ı' => 'i',
+        'ŀ' | 'ł' => 'l',
+        'ø' => 'o',
+        'ŧ' => 't',
+        c => c,
+    }
+}
@@ -1,103 +1,655 @@
-use std::num::ParseIntError;
+use std::fmt::Display;
 
-use xmltree::Element;
-use xmltree::ParseError;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use xml::common::{Position, TextPosition};
+use xml::reader::{EventReader, XmlEvent};
 
+use crate::constants::{LINEAR_MULTIPLIER, LOGARITHMIC_BASE};
+use crate::tools::media;
+use crate::words::is_semver;
+use crate::words::DictMetadata;
 use crate::words::Dictionary;
+use crate::words::DuplicateMode;
+use crate::words::EmptyField;
 use crate::words::ObscurityMode;
 use crate::words::Word;
+use crate::words::WordAttribute;
+use crate::words::{frequency_ranks, resolve_duplicates};
 
-#[derive(Debug)]
-pub enum DictParseError {
-    XMLParseError(ParseError),
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum DictParseErrorKind {
+    XMLParseError,
     InvalidXMLStructure,
     InvalidXMLData,
 }
 
-impl From<ParseError> for DictParseError {
-    fn from(error: ParseError) -> Self {
-        DictParseError::XMLParseError(error)
+#[derive(Debug, Clone, Serialize)]
+pub struct DictParseError {
+    pub kind: DictParseErrorKind,
+    pub line: u64,
+    pub column: u64,
+    pub word_index: Option<usize>,
+    pub tag: Option<String>,
+    pub message: String,
+}
+
+impl DictParseError {
+    fn new(
+        kind: DictParseErrorKind,
+        position: TextPosition,
+        word_index: Option<usize>,
+        tag: Option<&str>,
+        message: String,
+    ) -> DictParseError {
+        // xml-rs positions are zero-based, editors count from one
+        DictParseError {
+            kind,
+            line: position.row + 1,
+            column: position.column + 1,
+            word_index,
+            tag: tag.map(|t| t.to_owned()),
+            message,
+        }
     }
 }
 
-impl From<ParseIntError> for DictParseError {
-    fn from(_: ParseIntError) -> Self {
-        DictParseError::InvalidXMLData
+impl Display for DictParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "line {}, column {}", self.line, self.column)?;
+
+        if let Some(i) = self.word_index {
+            write!(f, " (word {})", i)?;
+        }
+
+        if let Some(tag) = &self.tag {
+            write!(f, " <{}>", tag)?;
+        }
+
+        write!(f, ": {}", self.message)
+    }
+}
+
+/// Minimal element tree that remembers where each element started in the source.
+struct Node {
+    name: String,
+    attributes: Vec<(String, String)>,
+    text: Option<String>,
+    children: Vec<Node>,
+    position: TextPosition,
+}
+
+impl Node {
+    fn get_child(&self, name: &str) -> Option<&Node> {
+        self.children.iter().find(|c| c.name == name)
+    }
+
+    fn get_children<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a Node> {
+        self.children.iter().filter(move |c| c.name == name)
+    }
+
+    fn get_attribute(&self, name: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, v)| v.as_str())
+    }
+}
+
+fn parse_tree<T: std::io::Read>(readable: T) -> Result<Node, DictParseError> {
+    let mut reader = EventReader::new(readable);
+    let mut stack: Vec<Node> = Vec::new();
+
+    loop {
+        let event = match reader.next() {
+            Ok(e) => e,
+            Err(e) => {
+                return Err(DictParseError::new(
+                    DictParseErrorKind::XMLParseError,
+                    e.position(),
+                    None,
+                    None,
+                    e.msg().to_owned(),
+                ))
+            }
+        };
+
+        match event {
+            XmlEvent::StartElement {
+                name, attributes, ..
+            } => stack.push(Node {
+                name: name.local_name,
+                attributes: attributes
+                    .into_iter()
+                    .map(|a| (a.name.local_name, a.value))
+                    .collect(),
+                text: None,
+                children: Vec::new(),
+                position: reader.position(),
+            }),
+            XmlEvent::Characters(s) | XmlEvent::CData(s) => {
+                if let Some(node) = stack.last_mut() {
+                    node.text.get_or_insert_with(String::new).push_str(&s);
+                }
+            }
+            XmlEvent::EndElement { .. } => {
+                let node = stack.pop().unwrap();
+
+                match stack.last_mut() {
+                    Some(parent) => parent.children.push(node),
+                    None => return Ok(node),
+                }
+            }
+            XmlEvent::EndDocument => {
+                return Err(DictParseError::new(
+                    DictParseErrorKind::XMLParseError,
+                    reader.position(),
+                    None,
+                    None,
+                    "Document has no root element".to_owned(),
+                ))
+            }
+            _ => (),
+        }
+    }
+}
+
+fn required_text(
+    word: &Node,
+    index: usize,
+    tag: &str,
+    errors: &mut Vec<DictParseError>,
+) -> Option<String> {
+    match word.get_child(tag) {
+        Some(e) => match &e.text {
+            Some(text) => Some(text.to_owned()),
+            None => {
+                errors.push(DictParseError::new(
+                    DictParseErrorKind::InvalidXMLData,
+                    e.position,
+                    Some(index),
+                    Some(tag),
+                    format!("<{}> must not be empty", tag),
+                ));
+                None
+            }
+        },
+        None => {
+            errors.push(DictParseError::new(
+                DictParseErrorKind::InvalidXMLStructure,
+                word.position,
+                Some(index),
+                Some(&word.name),
+                format!("Missing <{}> element", tag),
+            ));
+            None
+        }
     }
 }
 
+/// Reads the text of every `tag` element of a word, e.g. all `<sense>`s.
+fn text_list(
+    word: &Node,
+    index: usize,
+    tag: &str,
+    errors: &mut Vec<DictParseError>,
+) -> Vec<String> {
+    let mut out = Vec::new();
+
+    for e in word.get_children(tag) {
+        match e.text.as_deref().map(|t| t.trim()) {
+            Some(text) if !text.is_empty() => out.push(text.to_owned()),
+            _ => errors.push(DictParseError::new(
+                DictParseErrorKind::InvalidXMLData,
+                e.position,
+                Some(index),
+                Some(tag),
+                format!("<{}> must not be empty", tag),
+            )),
+        }
+    }
+
+    out
+}
+
+/// Reads the `<attribute name="...">` elements of a word, e.g. its gender or inflected forms.
+fn word_attributes(
+    word: &Node,
+    index: usize,
+    errors: &mut Vec<DictParseError>,
+) -> Vec<WordAttribute> {
+    let mut out: Vec<WordAttribute> = Vec::new();
+
+    for e in word.get_children("attribute") {
+        let name = match e.get_attribute("name").map(|n| n.trim()) {
+            Some(name) if !name.is_empty() => name.to_owned(),
+            _ => {
+                errors.push(DictParseError::new(
+                    DictParseErrorKind::InvalidXMLStructure,
+                    e.position,
+                    Some(index),
+                    Some("attribute"),
+                    "<attribute> needs a name".to_owned(),
+                ));
+                continue;
+            }
+        };
+
+        if out.iter().any(|a| a.name == name) {
+            errors.push(DictParseError::new(
+                DictParseErrorKind::InvalidXMLData,
+                e.position,
+                Some(index),
+                Some("attribute"),
+                format!("Attribute '{}' is given more than once", name),
+            ));
+            continue;
+        }
+
+        match e.text.as_deref().map(|t| t.trim()) {
+            Some(value) if !value.is_empty() => out.push(WordAttribute {
+                name,
+                value: value.to_owned(),
+            }),
+            _ => errors.push(DictParseError::new(
+                DictParseErrorKind::InvalidXMLData,
+                e.position,
+                Some(index),
+                Some("attribute"),
+                format!("Attribute '{}' must not be empty", name),
+            )),
+        }
+    }
+
+    out
+}
+
+/// Reads an optional media file name, which is relative to the media folder of the bundle.
+fn media_name(
+    word: &Node,
+    index: usize,
+    tag: &str,
+    errors: &mut Vec<DictParseError>,
+) -> Option<String> {
+    let e = word.get_child(tag)?;
+    let name = optional_text(word, tag)?;
+
+    if !media::is_valid_name(&name) {
+        errors.push(DictParseError::new(
+            DictParseErrorKind::InvalidXMLData,
+            e.position,
+            Some(index),
+            Some(tag),
+            format!(
+                "Invalid media file name '{}', must be a plain file name",
+                name
+            ),
+        ));
+        return None;
+    }
+
+    Some(name)
+}
+
+/// Reads the obscurity mode declared on the root element, e.g.
+/// `<dictionary obscurity="exponential" obscurity-factor="2.0">`.
+fn declared_obscurity_mode(root: &Node) -> Result<Option<ObscurityMode>, DictParseError> {
+    let mode = match root.get_attribute("obscurity") {
+        Some(m) => m,
+        None => return Ok(None),
+    };
+
+    let factor = match root.get_attribute("obscurity-factor") {
+        Some(f) => match f.trim().parse::<f64>() {
+            Ok(f) => Some(f),
+            Err(err) => {
+                return Err(DictParseError::new(
+                    DictParseErrorKind::InvalidXMLData,
+                    root.position,
+                    None,
+                    Some(&root.name),
+                    format!("Invalid obscurity-factor '{}': {}", f, err),
+                ))
+            }
+        },
+        None => None,
+    };
+
+    match mode.trim().to_lowercase().as_str() {
+        "linear" => Ok(Some(ObscurityMode::Linear(
+            factor.unwrap_or(LINEAR_MULTIPLIER),
+        ))),
+        "exponential" => Ok(Some(ObscurityMode::Exponential(
+            factor.unwrap_or(LOGARITHMIC_BASE),
+        ))),
+        "manual" => Ok(Some(ObscurityMode::Manual)),
+        "frequency" => Ok(Some(ObscurityMode::Frequency)),
+        other => Err(DictParseError::new(
+            DictParseErrorKind::InvalidXMLData,
+            root.position,
+            None,
+            Some(&root.name),
+            format!(
+                "Unknown obscurity mode '{}', expected linear, exponential, manual or frequency",
+                other
+            ),
+        )),
+    }
+}
+
+/// Optional word elements that are read as missing when they are left empty.
+const OPTIONAL_FIELDS: [&str; 5] = [
+    "pronunciation",
+    "part-of-speech",
+    "concept",
+    "obscurity",
+    "frequency",
+];
+
+fn optional_text(parent: &Node, tag: &str) -> Option<String> {
+    parent
+        .get_child(tag)
+        .and_then(|e| e.text.as_ref())
+        .map(|t| t.trim().to_owned())
+        .filter(|t| !t.is_empty())
+}
+
+fn timestamp(parent: &Node, tag: &str, errors: &mut Vec<DictParseError>) -> Option<i64> {
+    let e = parent.get_child(tag)?;
+    let text = optional_text(parent, tag)?;
+
+    match DateTime::parse_from_rfc3339(&text) {
+        Ok(t) => Some(t.timestamp()),
+        Err(err) => {
+            errors.push(DictParseError::new(
+                DictParseErrorKind::InvalidXMLData,
+                e.position,
+                None,
+                Some(tag),
+                format!("Invalid date '{}', expected RFC 3339: {}", text, err),
+            ));
+            None
+        }
+    }
+}
+
+/// Reads the optional `<metadata>` block. Missing fields are left empty, the creation date
+/// defaults to now.
+fn parse_metadata(root: &Node, errors: &mut Vec<DictParseError>) -> DictMetadata {
+    let now = Utc::now().timestamp();
+
+    let e = match root.get_child("metadata") {
+        Some(e) => e,
+        None => {
+            return DictMetadata {
+                created: Some(now),
+                ..Default::default()
+            }
+        }
+    };
+
+    let version = optional_text(e, "version");
+    if let Some(v) = &version {
+        if !is_semver(v) {
+            errors.push(DictParseError::new(
+                DictParseErrorKind::InvalidXMLData,
+                e.get_child("version").unwrap().position,
+                None,
+                Some("version"),
+                format!("Invalid version '{}', expected MAJOR.MINOR.PATCH", v),
+            ));
+        }
+    }
+
+    DictMetadata {
+        source_language: optional_text(e, "source-language"),
+        target_language: optional_text(e, "target-language"),
+        author: optional_text(e, "author"),
+        version,
+        description: optional_text(e, "description"),
+        license: optional_text(e, "license"),
+        created: timestamp(e, "created", errors).or(Some(now)),
+        modified: timestamp(e, "modified", errors),
+    }
+}
+
+/// Parses an xml dictionary, collecting every error found instead of stopping at the first.
+///
+/// If no mode is given, the one declared on the root element is used, falling back to linear.
 pub fn parse_xml_dictionary<T: std::io::Read>(
     readable: T,
-    mode: ObscurityMode,
-) -> Result<Dictionary, DictParseError> {
+    mode: Option<ObscurityMode>,
+    duplicates: DuplicateMode,
+) -> Result<Dictionary, Box<[DictParseError]>> {
+    parse_dictionary(readable, mode, duplicates, &mut Vec::new())
+}
+
+/// Like `parse_xml_dictionary`, but also returns the optional elements that were left empty,
+/// which the parser otherwise reads as missing.
+pub fn preview_xml_dictionary<T: std::io::Read>(
+    readable: T,
+    mode: Option<ObscurityMode>,
+    duplicates: DuplicateMode,
+) -> Result<(Dictionary, Vec<EmptyField>), Box<[DictParseError]>> {
+    let mut empty_fields = Vec::new();
+    let dict = parse_dictionary(readable, mode, duplicates, &mut empty_fields)?;
+
+    Ok((dict, empty_fields))
+}
+
+fn parse_dictionary<T: std::io::Read>(
+    readable: T,
+    mode: Option<ObscurityMode>,
+    duplicates: DuplicateMode,
+    empty_fields: &mut Vec<EmptyField>,
+) -> Result<Dictionary, Box<[DictParseError]>> {
     let mut out = Vec::new();
+    let mut frequencies = Vec::new();
+    // original <word> index and <word> position of every entry in `out`
+    let mut origins = Vec::new();
+    let mut errors = Vec::new();
 
-    let root = Element::parse(readable)?;
+    let root = parse_tree(readable).map_err(|e| vec![e].into_boxed_slice())?;
 
     if root.name != "dictionary" {
-        return Err(DictParseError::InvalidXMLStructure);
+        errors.push(DictParseError::new(
+            DictParseErrorKind::InvalidXMLStructure,
+            root.position,
+            None,
+            Some(&root.name),
+            "Root element must be <dictionary>".to_owned(),
+        ));
+        return Err(errors.into_boxed_slice());
     }
 
+    let declared = match declared_obscurity_mode(&root) {
+        Ok(m) => m,
+        Err(e) => {
+            errors.push(e);
+            None
+        }
+    };
+    let mode = mode
+        .or(declared)
+        .unwrap_or(ObscurityMode::Linear(LINEAR_MULTIPLIER));
+
     let title = match root.get_child("title") {
-        Some(e) => match e.get_text() {
-            Some(t) => t.to_string(),
-            None => return Err(DictParseError::InvalidXMLData),
+        Some(e) => match &e.text {
+            Some(t) => Some(t.to_string()),
+            None => {
+                errors.push(DictParseError::new(
+                    DictParseErrorKind::InvalidXMLData,
+                    e.position,
+                    None,
+                    Some("title"),
+                    "<title> must not be empty".to_owned(),
+                ));
+                None
+            }
         },
-        None => return Err(DictParseError::InvalidXMLStructure),
+        None => {
+            errors.push(DictParseError::new(
+                DictParseErrorKind::InvalidXMLStructure,
+                root.position,
+                None,
+                Some("dictionary"),
+                "Missing <title> element".to_owned(),
+            ));
+            None
+        }
     };
 
+    let metadata = parse_metadata(&root, &mut errors);
+
     let words = match root.get_child("words") {
         Some(e) => e,
-        None => return Err(DictParseError::InvalidXMLStructure),
+        None => {
+            errors.push(DictParseError::new(
+                DictParseErrorKind::InvalidXMLStructure,
+                root.position,
+                None,
+                Some("dictionary"),
+                "Missing <words> element".to_owned(),
+            ));
+            return Err(errors.into_boxed_slice());
+        }
     };
 
-    for child in &words.children {
-        let e = match child.as_element() {
-            Some(e) => e,
-            None => return Err(DictParseError::InvalidXMLStructure),
-        };
-
-        let text = match e.get_child("text") {
-            Some(e) => match e.get_text() {
-                Some(text) => text.to_string(),
-                None => return Err(DictParseError::InvalidXMLData),
-            },
-            None => return Err(DictParseError::InvalidXMLStructure),
-        };
+    for (i, e) in words.children.iter().enumerate() {
+        let text = required_text(e, i, "text", &mut errors);
+        let senses = text_list(e, i, "sense", &mut errors);
 
+        // the definition can be left out when the senses are given
         let definition = match e.get_child("definition") {
-            Some(e) => match e.get_text() {
-                Some(text) => text.to_string(),
-                None => return Err(DictParseError::InvalidXMLData),
-            },
-            None => return Err(DictParseError::InvalidXMLStructure),
+            None if !senses.is_empty() => Some(senses.join("; ")),
+            _ => required_text(e, i, "definition", &mut errors),
         };
 
         let pronunciation = match e.get_child("pronunciation") {
-            Some(e) => match e.get_text() {
-                Some(text) => Some(text.to_string()),
-                None => None,
-            },
+            Some(e) => e.text.to_owned(),
             None => None,
         };
 
         let obscurity = match e.get_child("obscurity") {
-            Some(e) => match e.get_text() {
-                Some(text) => text.to_string().parse::<u32>()?,
+            Some(o) => match &o.text {
+                Some(text) => match text.trim().parse::<u32>() {
+                    Ok(obs) => obs,
+                    Err(err) => {
+                        errors.push(DictParseError::new(
+                            DictParseErrorKind::InvalidXMLData,
+                            o.position,
+                            Some(i),
+                            Some("obscurity"),
+                            format!("Invalid obscurity '{}': {}", text, err),
+                        ));
+                        0u32
+                    }
+                },
                 None => 0u32,
             },
             None => 0u32,
         };
 
-        let wstruct = Word {
-            text,
-            pronunciation,
-            definition,
-            obscurity,
+        let frequency = match e.get_child("frequency") {
+            Some(f) => match &f.text {
+                Some(text) => match text.trim().parse::<u64>() {
+                    Ok(freq) => freq,
+                    Err(err) => {
+                        errors.push(DictParseError::new(
+                            DictParseErrorKind::InvalidXMLData,
+                            f.position,
+                            Some(i),
+                            Some("frequency"),
+                            format!("Invalid frequency '{}': {}", text, err),
+                        ));
+                        0u64
+                    }
+                },
+                None => 0u64,
+            },
+            None => 0u64,
         };
 
-        out.push(wstruct);
+        let audio = media_name(e, i, "audio", &mut errors);
+        let image = media_name(e, i, "image", &mut errors);
+
+        let part_of_speech = optional_text(e, "part-of-speech");
+        let concept = optional_text(e, "concept");
+        let examples = text_list(e, i, "example", &mut errors);
+        let synonyms = text_list(e, i, "synonym", &mut errors);
+        let antonyms = text_list(e, i, "antonym", &mut errors);
+        let tags = text_list(e, i, "tag", &mut errors);
+        let attributes = word_attributes(e, i, &mut errors);
+
+        if let (Some(text), Some(definition)) = (text, definition) {
+            for field in OPTIONAL_FIELDS {
+                if e.get_child(field).is_some() && optional_text(e, field).is_none() {
+                    empty_fields.push(EmptyField {
+                        word_index: i,
+                        text: text.to_owned(),
+                        field,
+                    });
+                }
+            }
+
+            let wstruct = Word {
+                text,
+                pronunciation,
+                definition,
+                obscurity,
+                audio,
+                image,
+                part_of_speech,
+                senses,
+                examples,
+                synonyms,
+                antonyms,
+                tags,
+                concept,
+                attributes,
+            };
+
+            out.push(wstruct);
+            frequencies.push(frequency);
+            origins.push((i, e.position));
+        }
+    }
+
+    let title = match title {
+        Some(title) if errors.is_empty() => title,
+        _ => return Err(errors.into_boxed_slice()),
+    };
+
+    if let ObscurityMode::Frequency = mode {
+        for (word, rank) in out.iter_mut().zip(frequency_ranks(&frequencies).iter()) {
+            word.obscurity = *rank;
+        }
     }
 
-    Ok(Dictionary::create(out.into_boxed_slice(), title, mode))
+    let out = match resolve_duplicates(out, duplicates) {
+        Ok(out) => out,
+        Err(rejected) => {
+            for (first, dup) in rejected.iter() {
+                let (index, position) = origins[*dup];
+                errors.push(DictParseError::new(
+                    DictParseErrorKind::InvalidXMLData,
+                    position,
+                    Some(index),
+                    Some("text"),
+                    format!(
+                        "Duplicate headword, first defined by word {}",
+                        origins[*first].0
+                    ),
+                ));
+            }
+
+            return Err(errors.into_boxed_slice());
+        }
+    };
+
+    let mut dict = Dictionary::create(out.into_boxed_slice(), title, mode);
+    dict.set_metadata(metadata);
+    dict.mark_imported();
+
+    Ok(dict)
 }
@@ -0,0 +1,184 @@
+use serde::Serialize;
+
+use super::{Dictionary, WordID};
+
+/// Shown in place of the word in a cloze card.
+pub const CLOZE_BLANK: &str = "_____";
+
+/// An example sentence with the word blanked out.
+#[derive(Debug, Clone, Serialize)]
+pub struct Cloze {
+    pub sentence: String,
+    /// The blanked out form, as written in the sentence
+    pub answer: String,
+}
+
+/// Byte ranges of the words of `text`. Apostrophes stay part of the word, like in corpus
+/// analysis.
+fn word_spans(text: &str) -> Vec<(usize, usize)> {
+    let mut spans = Vec::new();
+    let mut start = None;
+
+    for (i, c) in text
+        .char_indices()
+        .chain(std::iter::once((text.len(), ' ')))
+    {
+        match (start, c.is_alphanumeric() || c == '\'') {
+            (None, true) => start = Some(i),
+            (Some(s), false) => {
+                let word = &text[s..i];
+                let trimmed = word.trim_start_matches('\'');
+                let s = s + (word.len() - trimmed.len());
+                let e = s + trimmed.trim_end_matches('\'').len();
+
+                if s < e {
+                    spans.push((s, e));
+                }
+                start = None;
+            }
+            _ => (),
+        }
+    }
+
+    spans
+}
+
+impl Dictionary {
+    /// The example `example` of the word `id` with every occurrence of the headword, or one of
+    /// its inflected forms, blanked out. `None` if the word doesn't occur in it.
+    pub fn cloze(&self, id: WordID, example: usize) -> Option<Cloze> {
+        let word = self.get_word_from_id(id);
+        let sentence = word.examples.get(example)?;
+
+        let headword: Vec<String> = word_spans(&word.text)
+            .into_iter()
+            .map(|(s, e)| self.normalize(&word.text[s..e]))
+            .collect();
+        if headword.is_empty() {
+            return None;
+        }
+
+        let spans = word_spans(sentence);
+        let tokens: Vec<String> = spans
+            .iter()
+            .map(|(s, e)| self.normalize(&sentence[*s..*e]))
+            .collect();
+
+        // byte ranges to blank out, in order
+        let mut blanks: Vec<(usize, usize)> = Vec::new();
+        let mut i = 0;
+        while i < tokens.len() {
+            let len = headword.len();
+
+            if i + len <= tokens.len() && tokens[i..i + len] == headword[..] {
+                blanks.push((spans[i].0, spans[i + len - 1].1));
+                i += len;
+            } else if self.find_lemma(&tokens[i]).contains(&id) {
+                blanks.push(spans[i]);
+                i += 1;
+            } else {
+                i += 1;
+            }
+        }
+
+        let (first_start, first_end) = *blanks.first()?;
+        let answer = sentence[first_start..first_end].to_owned();
+
+        let mut out = String::new();
+        let mut last = 0;
+        for (s, e) in blanks {
+            out.push_str(&sentence[last..s]);
+            out.push_str(CLOZE_BLANK);
+            last = e;
+        }
+        out.push_str(&sentence[last..]);
+
+        Some(Cloze {
+            sentence: out,
+            answer,
+        })
+    }
+
+    /// Indices of the examples of `id` that make a cloze card.
+    pub fn cloze_examples(&self, id: WordID) -> Vec<usize> {
+        (0..self.get_word_from_id(id).examples.len())
+            .filter(|i| self.cloze(id, *i).is_some())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{dictionary::tests::word, ObscurityMode};
+    use super::*;
+
+    /// A dictionary of `text` with `examples`, and the id of the word.
+    fn dict_of(text: &str, examples: &[&str]) -> (Dictionary, WordID) {
+        let mut w = word(text, "");
+        w.examples = examples.iter().map(|e| e.to_string()).collect();
+
+        let dict = Dictionary::create(Box::new([w]), "Cloze".to_owned(), ObscurityMode::Manual);
+        let id = dict.get_word_ids()[0];
+
+        (dict, id)
+    }
+
+    fn blanked(dict: &Dictionary, id: WordID, example: usize) -> Option<(String, String)> {
+        dict.cloze(id, example).map(|c| (c.sentence, c.answer))
+    }
+
+    #[test]
+    fn blanks_every_occurrence() {
+        let (dict, id) = dict_of("bank", &["Bank after bank.", "No banking here.", "'bank'"]);
+
+        assert_eq!(
+            blanked(&dict, id, 0),
+            Some(("_____ after _____.".to_owned(), "Bank".to_owned()))
+        );
+        assert_eq!(blanked(&dict, id, 1), None);
+        assert_eq!(
+            blanked(&dict, id, 2),
+            Some(("'_____'".to_owned(), "bank".to_owned()))
+        );
+        assert_eq!(blanked(&dict, id, 3), None);
+        assert_eq!(dict.cloze_examples(id), [0, 2]);
+    }
+
+    #[test]
+    fn blanks_phrases_and_inflected_forms() {
+        let (dict, id) = dict_of("ice cream", &["I like ice  cream a lot."]);
+        assert_eq!(
+            blanked(&dict, id, 0),
+            Some(("I like _____ a lot.".to_owned(), "ice  cream".to_owned()))
+        );
+
+        let (mut dict, id) = dict_of("go", &["She went home."]);
+        assert_eq!(blanked(&dict, id, 0), None);
+
+        dict.insert_lemmas(vec![("went".to_owned(), id)]);
+        assert_eq!(
+            blanked(&dict, id, 0),
+            Some(("She _____ home.".to_owned(), "went".to_owned()))
+        );
+    }
+
+    #[test]
+    fn blanks_accented_words_only() {
+        let (dict, id) = dict_of("él", &["Él dijo que el libro es de él."]);
+
+        // the unaccented article is a different word
+        assert_eq!(
+            blanked(&dict, id, 0),
+            Some((
+                "_____ dijo que el libro es de _____.".to_owned(),
+                "Él".to_owned()
+            ))
+        );
+
+        let (dict, id) = dict_of("café", &["Un CAFÉ noir, pas un cafe."]);
+        assert_eq!(
+            blanked(&dict, id, 0),
+            Some(("Un _____ noir, pas un cafe.".to_owned(), "CAFÉ".to_owned()))
+        );
+    }
+}
@@ -4,57 +4,207 @@ use std::{
     sync::Mutex,
 };
 
+use chrono::Utc;
+use rand::Rng;
 use serde::{Deserialize, Serialize};
 
-use crate::{error::Error, program::filemanager, tools::crypt_string::PermutedString};
+use struct_version_manager::version_macro::version_mod;
 
-use super::Word;
+use crate::{error::Error, program::filemanager, tools::normalize};
+
+use super::{word::WordV1_0, DictMetadata, Word};
 
 const DICT_HEADER: &'static str = "DICTINARYDATA";
-const DICT_VERSION: &'static str = "1.0";
+const DICT_VERSION: &'static str = "1.1";
 
 pub enum FileVersion {
     Current,
     Old(String),
 }
 
+/// Stable identifier of a word that survives dictionary edits.
 #[derive(Clone, Copy, Debug, Hash, PartialEq, Eq, Serialize, Deserialize)]
 pub struct WordID {
-    id: usize,
+    id: u64,
+}
+
+/// Ids are kept below 2^53 so the frontend can round-trip them as plain numbers.
+const ID_MASK: u64 = (1 << 53) - 1;
+
+/// FNV-1a hash of `bytes`, masked to a frontend safe id.
+fn hash_id(bytes: impl Iterator<Item = u8>) -> u64 {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for b in bytes {
+        hash ^= b as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+
+    hash & ID_MASK
 }
 
 impl WordID {
-    fn from(id: usize) -> Self {
-        WordID { id }
+    /// Derives an id from the word text. `salt` separates homographs and collisions.
+    fn derive(text: &str, salt: u32) -> Self {
+        WordID {
+            id: hash_id(text.bytes().chain(salt.to_le_bytes())),
+        }
+    }
+
+    /// Reads an id from a file that still stored words by index.
+    pub(super) fn as_index(self) -> usize {
+        self.id as usize
     }
 }
 
-impl Into<usize> for WordID {
-    fn into(self) -> usize {
-        self.id
+/// Persistent identifier of a dictionary, stored in its file. Unlike the title it is unique and
+/// does not change when the dictionary is renamed.
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DictUID {
+    id: u64,
+}
+
+impl DictUID {
+    pub fn generate() -> Self {
+        DictUID {
+            id: rand::thread_rng().gen::<u64>() & ID_MASK,
+        }
+    }
+
+    /// Reads an id from its decimal form, as used in media urls.
+    pub fn parse(s: &str) -> Option<Self> {
+        let id = s.parse::<u64>().ok()?;
+
+        if id > ID_MASK {
+            return None;
+        }
+
+        Some(DictUID { id })
+    }
+
+    /// Id of a dictionary file from before dictionaries had ids. Derived from the title, so
+    /// every load of the same file agrees on it.
+    fn derive(title: &str) -> Self {
+        DictUID {
+            id: hash_id(title.bytes()),
+        }
     }
 }
 
-#[derive(Debug)]
+fn derive_ids(words: &[Word]) -> Box<[WordID]> {
+    let mut used = HashSet::new();
+
+    words
+        .iter()
+        .map(|w| {
+            let mut salt = 0;
+            loop {
+                let id = WordID::derive(&w.text, salt);
+                if used.insert(id) {
+                    break id;
+                }
+                salt += 1;
+            }
+        })
+        .collect()
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 pub enum ObscurityMode {
     Exponential(f64),
     Linear(f64),
     Manual,
+    /// Obscurity is ranked from raw corpus frequency counts, most frequent first.
+    Frequency,
 }
 
-#[derive(Serialize, Deserialize)]
-struct DictData {
-    name: PermutedString,
-    data: Box<[u8]>,
+/// How repeated headwords are handled when a dictionary is built.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub enum DuplicateMode {
+    /// Combine the definitions into the first entry.
+    Merge,
+    KeepFirst,
+    /// Keep every entry as a separate homograph with its own ID.
+    KeepAll,
+    Reject,
 }
 
+#[version_mod(DictData)]
+mod dict_data {
+    pub mod v1_0 {
+        use serde::{Deserialize, Serialize};
+        use struct_version_manager::version_macro::version;
+
+        use crate::tools::crypt_string::PermutedString;
+
+        #[derive(Serialize, Deserialize)]
+        #[version("1.0")]
+        pub struct DictData {
+            pub name: PermutedString,
+            pub data: Box<[u8]>,
+        }
+    }
+
+    pub mod v1_1 {
+        use serde::{Deserialize, Serialize};
+        use struct_version_manager::version_macro::version;
+
+        use crate::{
+            tools::crypt_string::PermutedString,
+            words::{DictMetadata, DictUID, WordID},
+        };
+
+        /// Adds the uid, metadata, word ids, lemma tables and when words were added.
+        #[derive(Serialize, Deserialize)]
+        #[version("1.1")]
+        pub struct DictData {
+            pub uid: DictUID,
+            pub name: PermutedString,
+            pub metadata: DictMetadata,
+            pub ids: Box<[WordID]>,
+            pub data: Box<[u8]>,
+            /// Inflected form -> headword
+            pub lemmas: Box<[(PermutedString, WordID)]>,
+            pub added: Box<[(WordID, i64)]>,
+        }
+    }
+}
+
+use dict_data::v1_1::DictData;
+
 impl DictData {
     pub fn size_of(&self) -> usize {
-        let size = self.name.len() + self.data.len() + std::mem::size_of::<Self>();
+        let size = self.name.len()
+            + self.metadata.size_of()
+            + self.ids.len() * std::mem::size_of::<WordID>()
+            + self.data.len()
+            + self
+                .lemmas
+                .iter()
+                .map(|(form, _)| form.len() + 2 * std::mem::size_of::<WordID>())
+                .sum::<usize>()
+            + self.added.len() * std::mem::size_of::<(WordID, i64)>()
+            + std::mem::size_of::<Self>();
         size
     }
 }
 
+/// What changed when a dictionary was updated from a new version of itself.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct DictDiff {
+    pub kept: usize,
+    pub added: usize,
+    pub removed: usize,
+}
+
+/// Appends the entries of `from` that `into` does not have yet.
+fn merge_list(into: &mut Vec<String>, from: Vec<String>) {
+    for s in from {
+        if !into.contains(&s) {
+            into.push(s);
+        }
+    }
+}
+
 fn insert_obs(obscurity_index: &mut BTreeMap<u32, Mutex<HashSet<WordID>>>, id: WordID, obs: u32) {
     let mut set = {
         if !obscurity_index.contains_key(&obs) {
@@ -67,44 +217,403 @@ fn insert_obs(obscurity_index: &mut BTreeMap<u32, Mutex<HashSet<WordID>>>, id: W
     set.insert(id);
 }
 
+/// Applies `mode` to repeated headwords. With `DuplicateMode::Reject`, returns the
+/// `(first, duplicate)` index pairs of every repeated headword instead.
+pub fn resolve_duplicates(
+    words: Vec<Word>,
+    mode: DuplicateMode,
+) -> Result<Vec<Word>, Box<[(usize, usize)]>> {
+    // headword -> (index in `words`, index in `out`)
+    let mut first_seen: HashMap<String, (usize, usize)> = HashMap::new();
+    let mut out: Vec<Word> = Vec::new();
+    let mut rejected = Vec::new();
+
+    for (i, word) in words.into_iter().enumerate() {
+        let (first, kept) = match first_seen.get(&word.text) {
+            Some(first) => *first,
+            None => {
+                first_seen.insert(word.text.to_owned(), (i, out.len()));
+                out.push(word);
+                continue;
+            }
+        };
+
+        match mode {
+            DuplicateMode::Merge => {
+                let existing = &mut out[kept];
+
+                if !existing
+                    .definition
+                    .split("; ")
+                    .any(|d| d == word.definition)
+                {
+                    existing.definition = format!("{}; {}", existing.definition, word.definition);
+                }
+                if existing.pronunciation.is_none() {
+                    existing.pronunciation = word.pronunciation;
+                }
+                if existing.audio.is_none() {
+                    existing.audio = word.audio;
+                }
+                if existing.image.is_none() {
+                    existing.image = word.image;
+                }
+                if existing.part_of_speech.is_none() {
+                    existing.part_of_speech = word.part_of_speech;
+                }
+                if existing.concept.is_none() {
+                    existing.concept = word.concept;
+                }
+                merge_list(&mut existing.senses, word.senses);
+                merge_list(&mut existing.examples, word.examples);
+                merge_list(&mut existing.synonyms, word.synonyms);
+                merge_list(&mut existing.antonyms, word.antonyms);
+                merge_list(&mut existing.tags, word.tags);
+                for attribute in word.attributes {
+                    if !existing.attributes.iter().any(|a| a.name == attribute.name) {
+                        existing.attributes.push(attribute);
+                    }
+                }
+                existing.obscurity = existing.obscurity.min(word.obscurity);
+            }
+            DuplicateMode::KeepFirst => (),
+            DuplicateMode::KeepAll => out.push(word),
+            DuplicateMode::Reject => rejected.push((first, i)),
+        }
+    }
+
+    if rejected.is_empty() {
+        Ok(out)
+    } else {
+        Err(rejected.into_boxed_slice())
+    }
+}
+
+/// Turns raw occurrence counts into obscurity ranks, so the most frequent word gets rank 1.
+/// Words with equal counts share a rank.
+pub fn frequency_ranks(frequencies: &[u64]) -> Box<[u32]> {
+    let mut order: Vec<usize> = (0..frequencies.len()).collect();
+    order.sort_by(|a, b| frequencies[*b].cmp(&frequencies[*a]));
+
+    let mut ranks = vec![0u32; frequencies.len()];
+    let mut rank = 0;
+    for (pos, i) in order.iter().enumerate() {
+        if pos == 0 || frequencies[*i] != frequencies[order[pos - 1]] {
+            rank = pos as u32 + 1;
+        }
+
+        ranks[*i] = rank;
+    }
+
+    ranks.into_boxed_slice()
+}
+
 #[derive(Debug)]
 pub struct Dictionary {
+    pub(super) uid: DictUID,
     pub(super) title: String,
+    pub(super) metadata: DictMetadata,
     pub(super) words: Box<[Word]>,
+    pub(super) ids: Box<[WordID]>,
+    pub(super) id_index: HashMap<WordID, usize>,
     pub(super) obscurity_index: BTreeMap<u32, Mutex<HashSet<WordID>>>,
-    pub(super) word_index: HashMap<String, WordID>,
+    pub(super) word_index: HashMap<String, Vec<WordID>>,
+    pub(super) concept_index: HashMap<String, Vec<WordID>>,
+    /// Normalized inflected form -> headwords
+    pub(super) lemmas: HashMap<String, Vec<WordID>>,
+    /// Unix seconds of when a word was added or came with the imported dictionary, unknown for
+    /// words from before 1.1
+    pub(super) added: HashMap<WordID, i64>,
 }
 
 impl Dictionary {
+    /// Creates a dictionary with ids derived from the word text.
     pub fn create(words: Box<[Word]>, name: String, mode: ObscurityMode) -> Dictionary {
+        let ids = derive_ids(&words);
+
+        Dictionary::create_with_ids(words, ids, name, mode)
+    }
+
+    /// Creates a dictionary from words that already have ids. `ids` must be parallel to `words`.
+    ///
+    /// The dictionary gets a new unique id.
+    pub fn create_with_ids(
+        words: Box<[Word]>,
+        ids: Box<[WordID]>,
+        name: String,
+        mode: ObscurityMode,
+    ) -> Dictionary {
         let mut dct = Dictionary {
+            uid: DictUID::generate(),
             words,
+            ids,
             title: name,
+            metadata: DictMetadata::default(),
+            id_index: HashMap::new(),
             obscurity_index: BTreeMap::new(),
             word_index: HashMap::new(),
+            concept_index: HashMap::new(),
+            lemmas: HashMap::new(),
+            added: HashMap::new(),
         };
+        let count = dct.words.len();
+        let mut last_obs = 0;
         for (i, word) in dct.words.iter_mut().enumerate() {
-            let id = WordID::from(i);
+            let id = dct.ids[i];
 
             let obs = match mode {
-                ObscurityMode::Exponential(base) => base.powi(i as i32) as u32,
-                ObscurityMode::Linear(multiplier) => (multiplier * i as f64) as u32 + 1,
-                ObscurityMode::Manual => word.obscurity,
+                // Kept below the room the words after it need, so the ranks of a long dictionary
+                // stay distinct instead of all saturating at u32::MAX
+                ObscurityMode::Exponential(base) => {
+                    let room = u32::MAX - (count - 1 - i) as u32;
+                    let obs = base.powi(i as i32).min(room as f64) as u32;
+                    obs.clamp(last_obs + 1, room)
+                }
+                // Saturates at u32::MAX rather than overflowing
+                ObscurityMode::Linear(multiplier) => (multiplier * i as f64 + 1.0) as u32,
+                // Frequency ranks are expected to already be filled in by the caller
+                ObscurityMode::Manual | ObscurityMode::Frequency => word.obscurity,
             };
 
             word.obscurity = obs;
+            last_obs = obs;
 
             insert_obs(&mut dct.obscurity_index, id, obs);
-            dct.word_index.insert(word.text.to_owned(), id);
+            dct.id_index.insert(id, i);
+            dct.word_index
+                .entry(word.text.to_owned())
+                .or_default()
+                .push(id);
+            if let Some(concept) = &word.concept {
+                dct.concept_index
+                    .entry(concept.to_owned())
+                    .or_default()
+                    .push(id);
+            }
         }
 
         dct
     }
 
+    /// Builds a copy of this dictionary with obscurity ranked by `frequencies`, most frequent
+    /// first. Words with equal counts keep their previous relative order.
+    pub fn rerank_by_frequency(&self, frequencies: &[u64]) -> Dictionary {
+        let mut words = self.words.clone();
+
+        let mut order: Vec<usize> = (0..words.len()).collect();
+        order.sort_by(|a, b| {
+            frequencies[*b]
+                .cmp(&frequencies[*a])
+                .then(words[*a].obscurity.cmp(&words[*b].obscurity))
+        });
+
+        for (rank, i) in order.iter().enumerate() {
+            words[*i].obscurity = rank as u32 + 1;
+        }
+
+        self.rebuilt(words.into_vec(), self.ids.to_vec(), self.title.to_owned())
+    }
+
+    /// Builds the updated version of this dictionary from `new`, keeping this title.
+    ///
+    /// Words are matched by headword (homographs in order), so matching words keep their id and
+    /// with it everyone's knowledge of them. New words get ids that neither an old word nor one
+    /// of the `archived` ids of removed words has.
+    pub fn updated_with(
+        &self,
+        new: Dictionary,
+        archived: &HashSet<WordID>,
+    ) -> (Dictionary, DictDiff) {
+        let mut used = archived.clone();
+        let mut seen: HashMap<&str, usize> = HashMap::new();
+        let mut ids = Vec::with_capacity(new.words.len());
+        let mut diff = DictDiff::default();
+        let mut added = Vec::new();
+
+        for word in new.words.iter() {
+            let n = seen.entry(word.text.as_str()).or_insert(0);
+
+            let id = match self.find_homographs(&word.text).get(*n) {
+                Some(id) => {
+                    diff.kept += 1;
+                    *id
+                }
+                None => {
+                    diff.added += 1;
+                    let id = self.unused_id(&word.text, &used);
+                    added.push(id);
+                    id
+                }
+            };
+
+            *n += 1;
+            used.insert(id);
+            ids.push(id);
+        }
+
+        diff.removed = self.words.len() - diff.kept;
+
+        // lemmas of the new version point at its own ids
+        let renamed: HashMap<WordID, WordID> =
+            new.ids.iter().copied().zip(ids.iter().copied()).collect();
+        let new_lemmas: Vec<(String, WordID)> = new
+            .lemma_entries()
+            .into_iter()
+            .filter_map(|(form, id)| Some((form, *renamed.get(&id)?)))
+            .collect();
+
+        let mut metadata = new.metadata;
+        metadata.created = self.metadata.created.or(metadata.created);
+        metadata.touch();
+
+        let mut dict = Dictionary::create_with_ids(
+            new.words,
+            ids.into_boxed_slice(),
+            self.title.to_owned(),
+            ObscurityMode::Manual,
+        );
+        dict.uid = self.uid;
+        dict.metadata = metadata;
+        dict.insert_lemmas(self.lemma_entries());
+        dict.insert_lemmas(new_lemmas);
+        dict.keep_added(self);
+        dict.mark_added(&added);
+
+        (dict, diff)
+    }
+
+    /// Builds an edited copy of this dictionary, keeping its id, metadata and the lemmas of the
+    /// words that are left.
+    pub(super) fn rebuilt(&self, words: Vec<Word>, ids: Vec<WordID>, title: String) -> Dictionary {
+        let mut dict = Dictionary::create_with_ids(
+            words.into_boxed_slice(),
+            ids.into_boxed_slice(),
+            title,
+            ObscurityMode::Manual,
+        );
+        dict.uid = self.uid;
+        dict.metadata = self.metadata.clone();
+        dict.metadata.touch();
+        dict.insert_lemmas(self.lemma_entries());
+        dict.keep_added(self);
+
+        dict
+    }
+
+    /// Takes over when the words that are still here were added to `old`.
+    fn keep_added(&mut self, old: &Dictionary) {
+        for (id, time) in old.added.iter() {
+            if self.contains_id(*id) {
+                self.added.insert(*id, *time);
+            }
+        }
+    }
+
+    /// Records `ids` as added now.
+    fn mark_added(&mut self, ids: &[WordID]) {
+        let now = Utc::now().timestamp();
+
+        for id in ids {
+            self.added.insert(*id, now);
+        }
+    }
+
+    /// Records every word as added now, for a dictionary that was just imported.
+    pub fn mark_imported(&mut self) {
+        let ids = self.ids.to_vec();
+        self.mark_added(&ids);
+    }
+
+    /// Unix seconds of when the word was added to this dictionary, `None` if unknown.
+    pub fn get_added(&self, id: WordID) -> Option<i64> {
+        self.added.get(&id).copied()
+    }
+
+    /// Appends `word` with an id that neither a word of this dictionary nor one of the
+    /// `archived` ids of removed words has.
+    pub fn with_word_added(&self, word: Word, archived: &HashSet<WordID>) -> (Dictionary, WordID) {
+        let id = self.unused_id(&word.text, archived);
+
+        let mut words = self.words.to_vec();
+        let mut ids = self.ids.to_vec();
+        words.push(word);
+        ids.push(id);
+
+        let mut dict = self.rebuilt(words, ids, self.title.to_owned());
+        dict.mark_added(&[id]);
+
+        (dict, id)
+    }
+
+    pub fn with_word_replaced(&self, id: WordID, word: Word) -> Result<Dictionary, Error> {
+        let i = *self.id_index.get(&id).ok_or("Word not found!")?;
+
+        let mut words = self.words.to_vec();
+        words[i] = word;
+
+        Ok(self.rebuilt(words, self.ids.to_vec(), self.title.to_owned()))
+    }
+
+    pub fn without_word(&self, id: WordID) -> Result<Dictionary, Error> {
+        let i = *self.id_index.get(&id).ok_or("Word not found!")?;
+
+        let mut words = self.words.to_vec();
+        let mut ids = self.ids.to_vec();
+        words.remove(i);
+        ids.remove(i);
+
+        Ok(self.rebuilt(words, ids, self.title.to_owned()))
+    }
+
+    pub fn with_title(&self, title: String) -> Dictionary {
+        self.rebuilt(self.words.to_vec(), self.ids.to_vec(), title)
+    }
+
+    /// Derives an id for `text` that is neither in this dictionary nor in `used`.
+    pub(super) fn unused_id(&self, text: &str, used: &HashSet<WordID>) -> WordID {
+        let mut salt = 0;
+        loop {
+            let id = WordID::derive(text, salt);
+            if !self.contains_id(id) && !used.contains(&id) {
+                return id;
+            }
+            salt += 1;
+        }
+    }
+
     pub fn get_title(&self) -> &str {
         &self.title
     }
 
+    pub fn get_uid(&self) -> DictUID {
+        self.uid
+    }
+
+    /// Gives this dictionary a fresh id, for copies that must not share one with the original.
+    pub fn regenerate_uid(&mut self) {
+        self.uid = DictUID::generate();
+    }
+
+    pub fn get_metadata(&self) -> &DictMetadata {
+        &self.metadata
+    }
+
+    pub fn set_metadata(&mut self, metadata: DictMetadata) {
+        self.metadata = metadata;
+    }
+
+    /// Normalizes a headword or typed answer with the rules of the source language.
+    pub fn normalize(&self, text: &str) -> String {
+        normalize::normalize(text, self.metadata.source_language.as_deref())
+    }
+
+    pub fn get_max_obscurity(&self) -> u32 {
+        match self.obscurity_index.keys().next_back() {
+            Some(obs) => *obs,
+            None => 0,
+        }
+    }
+
     pub fn get_words_leq_score(&self, score: u32) -> Box<[WordID]> {
         let mut v = Vec::new();
 
@@ -120,34 +629,49 @@ impl Dictionary {
         v.into_boxed_slice()
     }
 
+    /// Returns the first entry for `word`, or for the headword it is an inflected form of. See
+    /// `find_homographs` for every entry.
     pub fn find_word(&self, word: String) -> Option<WordID> {
         match self.word_index.get(&word) {
-            Some(id) => Some(*id),
-            None => None,
+            Some(ids) => ids.first().copied(),
+            None => self.find_lemma(&word).first().copied(),
         }
     }
 
-    fn get_id_from_index(&self, index: usize) -> Option<WordID> {
-        if index >= self.words.len() {
-            return None;
+    pub fn find_homographs(&self, word: &str) -> &[WordID] {
+        match self.word_index.get(word) {
+            Some(ids) => ids,
+            None => &[],
         }
+    }
 
-        Some(WordID { id: index })
+    /// Words linked to `concept`, in this dictionary.
+    pub fn find_concept(&self, concept: &str) -> &[WordID] {
+        match self.concept_index.get(concept) {
+            Some(ids) => ids,
+            None => &[],
+        }
     }
 
-    pub fn get_word_from_id(&self, id: WordID) -> &Word {
-        &self.words[id.id]
+    pub(super) fn get_id_from_index(&self, index: usize) -> Option<WordID> {
+        self.ids.get(index).copied()
     }
 
-    pub fn get_word_ids(&self) -> Box<[WordID]> {
-        let mut ids = Vec::new();
-        ids.reserve(self.words.len());
+    pub fn contains_id(&self, id: WordID) -> bool {
+        self.id_index.contains_key(&id)
+    }
 
-        for (i, _) in self.words.iter().enumerate() {
-            ids.push(WordID::from(i));
-        }
+    pub fn get_word_from_id(&self, id: WordID) -> &Word {
+        &self.words[self.id_index[&id]]
+    }
 
-        ids.into_boxed_slice()
+    /// File names of every audio clip and image the words refer to.
+    pub fn get_media(&self) -> Box<[&str]> {
+        self.words.iter().flat_map(|w| w.get_media()).collect()
+    }
+
+    pub fn get_word_ids(&self) -> Box<[WordID]> {
+        self.ids.clone()
     }
 
     pub fn save_to<T: Write>(&self, writable: &mut T) -> Result<(), Error> {
@@ -158,8 +682,17 @@ impl Dictionary {
         let data = alloc.into_boxed_slice();
 
         let data = DictData {
+            uid: self.uid,
             name: self.title.to_owned().into(),
+            metadata: self.metadata.clone(),
+            ids: self.ids.clone(),
             data,
+            lemmas: self
+                .lemma_entries()
+                .into_iter()
+                .map(|(form, id)| (form.into(), id))
+                .collect(),
+            added: self.added.iter().map(|(id, t)| (*id, *t)).collect(),
         };
 
         let mut final_alloc = vec![0u8; data.size_of()];
@@ -177,7 +710,7 @@ impl Dictionary {
     }
 
     pub fn load_from<'a, T: Read>(readable: &mut T) -> Result<(Dictionary, FileVersion), Error> {
-        let mut file = filemanager::read_file(readable)?;
+        let file = filemanager::read_file(readable)?;
 
         if file.header != DICT_HEADER {
             return Err("Invalid File Header!")?;
@@ -185,28 +718,247 @@ impl Dictionary {
 
         match file.version.as_str() {
             DICT_VERSION => {
-                let data = &mut file.data[..];
+                let data: DictData = postcard::from_bytes(&file.data)?;
+
+                let words: Box<[Word]> = postcard::from_bytes(&data.data)?;
+                if words.len() != data.ids.len() {
+                    return Err("Corrupt dictionary: word and id count differ!")?;
+                }
+
+                let mut dict = Dictionary::create_with_ids(
+                    words,
+                    data.ids,
+                    data.name.to_string(),
+                    ObscurityMode::Manual,
+                );
+                dict.uid = data.uid;
+                dict.metadata = data.metadata;
+                dict.insert_lemmas(
+                    data.lemmas
+                        .into_vec()
+                        .into_iter()
+                        .map(|(form, id)| (form.to_string(), id)),
+                );
+                dict.added = data.added.iter().copied().collect();
+
+                Ok((dict, FileVersion::Current))
+            }
+            "1.0" => {
+                let data: dict_data::v1_0::DictData = postcard::from_bytes(&file.data)?;
 
-                let dict_data: DictData = postcard::from_bytes(&data)?;
+                let words: Vec<WordV1_0> = postcard::from_bytes(&data.data)?;
+                let words = words.into_iter().map(Word::from).collect();
 
-                let words = postcard::from_bytes(&dict_data.data)?;
-                let name = dict_data.name;
+                // ids are derived from the text, so every load of this file agrees on them, and
+                // so is the uid from the title
+                let mut dict =
+                    Dictionary::create(words, data.name.to_string(), ObscurityMode::Manual);
+                dict.uid = DictUID::derive(&dict.title);
 
-                Ok((
-                    Dictionary::create(words, name.to_string(), ObscurityMode::Manual),
-                    FileVersion::Current,
-                ))
+                Ok((dict, FileVersion::Old("1.0".to_owned())))
             }
             v => {
-                let dict = match v {
-                    _ => {
-                        println!("{}", v);
-                        Err("Unknown File Version!")?
-                    }
-                };
-
-                Ok((dict, FileVersion::Old(v.to_owned())))
+                println!("{}", v);
+                Err("Unknown File Version!")?
             }
         }
     }
 }
+
+#[cfg(test)]
+pub(super) mod tests {
+    use super::*;
+
+    fn to_bytes<T: Serialize>(value: &T) -> Box<[u8]> {
+        let mut buf = vec![0u8; 4096];
+        let len = postcard::to_slice(value, &mut buf).unwrap().len();
+        buf.truncate(len);
+
+        buf.into_boxed_slice()
+    }
+
+    /// A word with only a headword and definition.
+    pub(in super::super) fn word(text: &str, definition: &str) -> Word {
+        WordV1_0 {
+            text: text.to_owned(),
+            definition: definition.to_owned(),
+            pronunciation: None,
+            obscurity: 0,
+        }
+        .into()
+    }
+
+    fn obscurities(dict: &Dictionary) -> Vec<u32> {
+        dict.words.iter().map(|w| w.obscurity).collect()
+    }
+
+    /// A dictionary file as the 1.0 app wrote it, with `(text, definition)` words.
+    pub(in super::super) fn v1_0_file(title: &str, words: &[(&str, &str)]) -> Vec<u8> {
+        let words: Vec<WordV1_0> = words
+            .iter()
+            .map(|(text, definition)| WordV1_0 {
+                text: text.to_string(),
+                definition: definition.to_string(),
+                pronunciation: None,
+                obscurity: 0,
+            })
+            .collect();
+        let data = dict_data::v1_0::DictData {
+            name: title.to_owned().into(),
+            data: to_bytes(&words),
+        };
+
+        let mut file = Vec::new();
+        filemanager::save_file(
+            &mut file,
+            DICT_HEADER.to_owned(),
+            "1.0".to_owned(),
+            to_bytes(&data),
+        )
+        .unwrap();
+
+        file
+    }
+
+    fn load(file: &[u8]) -> Dictionary {
+        Dictionary::load_from(&mut &file[..]).unwrap().0
+    }
+
+    #[test]
+    fn loads_1_0_with_stable_ids() {
+        let file = v1_0_file(
+            "Old",
+            &[("bank", "money"), ("river", "water"), ("bank", "shore")],
+        );
+        let (dict, version) = Dictionary::load_from(&mut &file[..]).unwrap();
+
+        assert!(matches!(version, FileVersion::Old(v) if v == "1.0"));
+        assert_eq!(dict.get_title(), "Old");
+
+        let ids = dict.get_word_ids();
+        let definitions: Vec<&str> = ids
+            .iter()
+            .map(|id| dict.get_word_from_id(*id).definition.as_str())
+            .collect();
+        assert_eq!(definitions, ["money", "water", "shore"]);
+        assert_eq!(dict.find_homographs("bank"), [ids[0], ids[2]]);
+        assert_ne!(ids[0], ids[2]);
+
+        // a restart has to find the same words and dictionary again
+        let again = load(&file);
+        assert_eq!(again.get_word_ids(), ids);
+        assert_eq!(again.get_uid(), dict.get_uid());
+
+        let other = load(&v1_0_file("Other", &[("bank", "money")]));
+        assert_ne!(other.get_uid(), dict.get_uid());
+    }
+
+    #[test]
+    fn keeps_ids_when_saved_as_current() {
+        let old = load(&v1_0_file("Old", &[("bank", "money"), ("bank", "shore")]));
+
+        let mut file = Vec::new();
+        old.save_to(&mut file).unwrap();
+        let (dict, version) = Dictionary::load_from(&mut &file[..]).unwrap();
+
+        assert!(matches!(version, FileVersion::Current));
+        assert_eq!(dict.get_uid(), old.get_uid());
+        assert_eq!(dict.get_word_ids(), old.get_word_ids());
+    }
+
+    #[test]
+    fn ranks_frequencies() {
+        assert_eq!(*frequency_ranks(&[5, 20, 5, 1, 20]), [3, 1, 3, 5, 1]);
+        assert_eq!(*frequency_ranks(&[7]), [1]);
+        assert_eq!(*frequency_ranks(&[u64::MAX, 0, u64::MAX]), [1, 3, 1]);
+        assert!(frequency_ranks(&[]).is_empty());
+    }
+
+    #[test]
+    fn exponential_ranks_stay_distinct_past_u32() {
+        let words = (0..40).map(|i| word(&format!("w{}", i), "")).collect();
+        let dict = Dictionary::create(words, "Exp".to_owned(), ObscurityMode::Exponential(2.0));
+        let obs = obscurities(&dict);
+
+        assert_eq!(obs[..4], [1, 2, 4, 8]);
+        assert!(obs.windows(2).all(|w| w[0] < w[1]));
+        assert_eq!(obs[39], u32::MAX);
+        assert_eq!(dict.obscurity_index.len(), 40);
+
+        let empty = Dictionary::create(
+            Box::new([]),
+            "Empty".to_owned(),
+            ObscurityMode::Exponential(2.0),
+        );
+        assert_eq!(empty.get_max_obscurity(), 0);
+    }
+
+    #[test]
+    fn linear_ranks_saturate_past_u32() {
+        let words = (0..6).map(|i| word(&format!("w{}", i), "")).collect();
+        let dict = Dictionary::create(words, "Lin".to_owned(), ObscurityMode::Linear(1e9));
+
+        assert_eq!(
+            obscurities(&dict),
+            [
+                1,
+                1_000_000_001,
+                2_000_000_001,
+                3_000_000_001,
+                4_000_000_001,
+                u32::MAX
+            ]
+        );
+    }
+
+    fn texts(words: &[Word]) -> Vec<&str> {
+        words.iter().map(|w| w.text.as_str()).collect()
+    }
+
+    fn banks() -> Vec<Word> {
+        let mut shore = word("bank", "shore");
+        shore.pronunciation = Some("bæŋk".to_owned());
+        shore.examples = vec!["a river bank".to_owned(), "the bank of the lake".to_owned()];
+
+        let mut money = word("bank", "money");
+        money.examples = vec!["a river bank".to_owned()];
+
+        vec![money, word("river", "water"), shore, word("bank", "money")]
+    }
+
+    #[test]
+    fn merges_duplicates_into_first() {
+        let merged = resolve_duplicates(banks(), DuplicateMode::Merge).unwrap();
+
+        assert_eq!(texts(&merged), ["bank", "river"]);
+        assert_eq!(merged[0].definition, "money; shore");
+        assert_eq!(merged[0].pronunciation.as_deref(), Some("bæŋk"));
+        assert_eq!(merged[0].examples, ["a river bank", "the bank of the lake"]);
+    }
+
+    #[test]
+    fn first_duplicate_keeps_its_id() {
+        let merged = resolve_duplicates(banks(), DuplicateMode::Merge).unwrap();
+        let dict = Dictionary::create(merged.into(), "Merged".to_owned(), ObscurityMode::Manual);
+        assert_eq!(dict.find_homographs("bank"), [WordID::derive("bank", 0)]);
+
+        let first = resolve_duplicates(banks(), DuplicateMode::KeepFirst).unwrap();
+        assert_eq!(texts(&first), ["bank", "river"]);
+        assert_eq!(first[0].definition, "money");
+
+        let all = resolve_duplicates(banks(), DuplicateMode::KeepAll).unwrap();
+        let dict = Dictionary::create(all.into(), "All".to_owned(), ObscurityMode::Manual);
+        let homographs = dict.find_homographs("bank");
+        assert_eq!(homographs[0], WordID::derive("bank", 0));
+        assert_eq!(dict.get_word_from_id(homographs[0]).definition, "money");
+        assert_eq!(dict.get_word_from_id(homographs[1]).definition, "shore");
+        assert_eq!(homographs.len(), 3);
+    }
+
+    #[test]
+    fn rejects_duplicates_with_indices() {
+        let rejected = resolve_duplicates(banks(), DuplicateMode::Reject).unwrap_err();
+
+        assert_eq!(*rejected, [(0, 2), (0, 3)]);
+    }
+}
@@ -0,0 +1,89 @@
+use chrono::{DateTime, Duration, Utc};
+use serde::Deserialize;
+
+use super::{Dictionary, Knowledge, WordID};
+
+/// Picks the words of a custom practice session. Every set criterion has to match, unset ones
+/// match everything.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct WordFilter {
+    /// Words with at least one of these tags
+    pub tags: Vec<String>,
+    /// Words with one of these parts of speech
+    pub parts_of_speech: Vec<String>,
+    pub min_obscurity: Option<u32>,
+    pub max_obscurity: Option<u32>,
+    /// Words answered wrong within this many days
+    pub failed_within_days: Option<u32>,
+    /// Words added to the dictionary within this many days
+    pub added_within_days: Option<u32>,
+    /// Only these words, e.g. picked by hand from the dictionary browser
+    pub words: Option<Vec<WordID>>,
+}
+
+/// Whether `list` contains `s`, ignoring case.
+fn contains_ignore_case(list: &[String], s: &str) -> bool {
+    list.iter().any(|l| l.to_lowercase() == s.to_lowercase())
+}
+
+/// Whether `time` lies within `days` days before `now`.
+fn within_days(time: Option<i64>, days: u32, now: DateTime<Utc>) -> bool {
+    match time {
+        Some(time) => time >= (now - Duration::days(days as i64)).timestamp(),
+        None => false,
+    }
+}
+
+impl WordFilter {
+    pub fn matches(
+        &self,
+        id: WordID,
+        dict: &Dictionary,
+        knowledge: &Knowledge,
+        now: DateTime<Utc>,
+    ) -> bool {
+        let word = dict.get_word_from_id(id);
+
+        if let Some(words) = &self.words {
+            if !words.contains(&id) {
+                return false;
+            }
+        }
+
+        let tagged = word
+            .tags
+            .iter()
+            .any(|t| contains_ignore_case(&self.tags, t));
+        if !self.tags.is_empty() && !tagged {
+            return false;
+        }
+
+        if !self.parts_of_speech.is_empty() {
+            match &word.part_of_speech {
+                Some(pos) if contains_ignore_case(&self.parts_of_speech, pos) => (),
+                _ => return false,
+            }
+        }
+
+        if self.min_obscurity.map_or(false, |min| word.obscurity < min)
+            || self.max_obscurity.map_or(false, |max| word.obscurity > max)
+        {
+            return false;
+        }
+
+        if let Some(days) = self.failed_within_days {
+            if !within_days(knowledge.get_last_failure(id), days, now) {
+                return false;
+            }
+        }
+
+        if let Some(days) = self.added_within_days {
+            if !within_days(dict.get_added(id), days, now) {
+                return false;
+            }
+        }
+
+        true
+    }
+}
@@ -1,23 +1,29 @@
 use std::{
+    collections::{HashMap, HashSet},
     io::{Read, Write},
     mem::size_of,
     sync::Arc,
 };
 
-use chrono::{DateTime, Utc};
-use serde::{de::Visitor, Deserializer, Serializer};
+use chrono::{DateTime, Duration, Utc};
+use serde::{de::Visitor, Deserializer, Serialize, Serializer};
 
 use struct_version_manager::version_macro::version_mod;
 
-use crate::{error::Error, program::filemanager, tools::dict_map::DictMap};
+use crate::{
+    constants::LEECH_LAPSES, error::Error, program::filemanager, tools::dict_map::DictMap,
+};
 
 use super::{Dictionary, WordID};
 
 const KNOW_HEADER: &'static str = "KNOWLEDGEDATA";
-const KNOW_VERSION: &'static str = "0.2";
+const KNOW_VERSION: &'static str = "0.3";
 
 const MIN_HALF_LIFE: f32 = 10.0;
 
+/// Words are due for practice once their recall probability drops below this.
+pub const RECALL_THRESHOLD: f32 = 0.6;
+
 struct TimeVisitor;
 
 impl<'de> Visitor<'de> for TimeVisitor {
@@ -79,6 +85,7 @@ mod word_knowledge {
 
         use crate::words::WordID;
 
+        /// In 0.2 `word_id` is an index into the dictionary, since 0.3 it is a stable id.
         #[derive(Clone, Debug, Serialize, Deserialize)]
         #[version("0.2")]
         pub struct WordKnowledge {
@@ -100,12 +107,46 @@ mod word_knowledge {
             pub knowledge_data: Box<[u8]>,
         }
     }
+
+    pub mod v0_3 {
+        use serde::{Deserialize, Serialize};
+        use struct_version_manager::version_macro::version;
+
+        use super::v0_2::WordKnowledge;
+
+        use crate::words::{DictUID, WordID};
+
+        /// Points at the dictionary by id instead of by title, and adds suspended words, lapses,
+        /// the last wrong answers, burials, attributes and cloze cards.
+        #[derive(Serialize, Deserialize)]
+        #[version("0.3")]
+        pub struct KnowledgeData {
+            pub dict_id: DictUID,
+            pub active_words: usize,
+            pub knowledge_data: Box<[u8]>,
+            pub suspended: Box<[WordID]>,
+            pub lapses: Box<[(WordID, u32)]>,
+            pub failures: Box<[(WordID, i64)]>,
+            pub buried: Box<[(WordID, i64)]>,
+            pub attributes: Box<[(String, WordKnowledge)]>,
+            pub cloze: Box<[WordKnowledge]>,
+        }
+    }
 }
 
-use word_knowledge::v0_2::KnowledgeData;
 pub use word_knowledge::v0_2::WordKnowledge;
+use word_knowledge::v0_3::KnowledgeData;
 
 impl WordKnowledge {
+    fn new(word_id: WordID) -> WordKnowledge {
+        WordKnowledge {
+            word_id,
+            last_practice: None,
+            half_life: MIN_HALF_LIFE,
+            _pv: (),
+        }
+    }
+
     pub fn calculate_p_value(&self, practice_time: DateTime<Utc>) -> f32 {
         if self.last_practice.is_none() {
             return 0.0;
@@ -115,51 +156,177 @@ impl WordKnowledge {
 
         2.0f32.powf((-delta) / self.half_life)
     }
+
+    /// When the recall probability drops below `RECALL_THRESHOLD`, `None` if never practiced.
+    pub fn calculate_due(&self) -> Option<DateTime<Utc>> {
+        let minutes = -self.half_life * RECALL_THRESHOLD.log2();
+
+        Some(self.last_practice? + Duration::seconds((minutes * 60.0) as i64))
+    }
+
+    /// Adjusts the half-life to an answer given now. Returns whether the answer was a lapse,
+    /// i.e. wrong after having been practiced before.
+    fn record_answer(&mut self, correct: bool) -> bool {
+        let lp = self.last_practice;
+        self.last_practice = Some(Utc::now());
+
+        match lp {
+            Some(time) => {
+                let time_delta = self.last_practice.unwrap() - time;
+                // > 1 if practiced after expected half-life, < 1 if practiced before
+                let time_factor = time_delta.num_minutes() as f32 / self.half_life;
+
+                // A 0 time factor will result in no change, a 1 time factor will change by a factor of 2
+                let mut multiplier = 1.0 + 1.0 * time_factor;
+
+                // If the answer was wrong, take reciprocal
+                if !correct {
+                    multiplier = multiplier.recip();
+                }
+
+                self.half_life = f32::max(self.half_life * multiplier, MIN_HALF_LIFE);
+            }
+            None => {
+                if correct {
+                    // Set half-life to two days if user alerady knows word the first time seeing it.
+                    self.half_life = 60.0 * 24.0 * 2.0;
+                } else {
+                    self.half_life = MIN_HALF_LIFE;
+                }
+            }
+        }
+
+        lp.is_some() && !correct
+    }
+}
+
+/// How well the user knows a word, for display next to it.
+#[derive(Debug, Clone, Serialize)]
+pub struct WordStatus {
+    /// Minutes
+    pub half_life: f32,
+    /// Unix seconds, `None` if the word was never practiced
+    pub last_practice: Option<i64>,
+    /// Probability of recalling the word right now
+    pub recall: f32,
+    /// Unix seconds, `None` if the word was never practiced
+    pub due: Option<i64>,
+    pub lapses: u32,
+    /// Left out of practice sessions
+    pub suspended: bool,
+    /// Answered wrong so often that it probably needs a different approach, like a mnemonic
+    pub leech: bool,
 }
 
 pub struct Knowledge {
     dict: Arc<Dictionary>,
-    knowledge: Box<[WordKnowledge]>,
+    /// Keyed by word id. Entries of words removed from the dictionary stay here as an archive,
+    /// their ids aren't given to new words.
+    knowledge: HashMap<WordID, WordKnowledge>,
     active_words: usize,
+    suspended: HashSet<WordID>,
+    /// Number of wrong answers to words that had been practiced before
+    lapses: HashMap<WordID, u32>,
+    /// Unix seconds of the last wrong answer
+    failures: HashMap<WordID, i64>,
+    /// Unix seconds until which a word is left out of reviews
+    buried: HashMap<WordID, i64>,
+    /// Keyed by word id and attribute name, only for attributes that were drilled
+    attributes: HashMap<(WordID, String), WordKnowledge>,
+    /// Knowledge of words in context, only for words that had a cloze card
+    cloze: HashMap<WordID, WordKnowledge>,
 }
 
 impl Knowledge {
     pub fn create(dict: Arc<Dictionary>) -> Knowledge {
-        let mut knowledge = Vec::new();
-        knowledge.reserve(dict.words.len());
-
-        for id in dict.get_word_ids().as_ref() {
-            let k = WordKnowledge {
-                word_id: *id,
-                last_practice: None,
-                half_life: MIN_HALF_LIFE,
-                _pv: (),
-            };
-
-            knowledge.push(k);
-        }
-
-        Knowledge {
+        let mut knowledge = Knowledge {
             dict,
-            knowledge: knowledge.into_boxed_slice(),
+            knowledge: HashMap::new(),
             active_words: 0,
+            suspended: HashSet::new(),
+            lapses: HashMap::new(),
+            failures: HashMap::new(),
+            buried: HashMap::new(),
+            attributes: HashMap::new(),
+            cloze: HashMap::new(),
+        };
+        knowledge.fill_missing();
+
+        knowledge
+    }
+
+    fn from_entries(
+        dict: Arc<Dictionary>,
+        entries: impl IntoIterator<Item = WordKnowledge>,
+        active_words: usize,
+    ) -> Knowledge {
+        let mut knowledge = Knowledge {
+            dict,
+            knowledge: entries.into_iter().map(|k| (k.word_id, k)).collect(),
+            active_words,
+            suspended: HashSet::new(),
+            lapses: HashMap::new(),
+            failures: HashMap::new(),
+            buried: HashMap::new(),
+            attributes: HashMap::new(),
+            cloze: HashMap::new(),
+        };
+        knowledge.fill_missing();
+
+        knowledge
+    }
+
+    /// Adds fresh entries for dictionary words that have none yet.
+    fn fill_missing(&mut self) {
+        for id in self.dict.ids.iter() {
+            if !self.knowledge.contains_key(id) {
+                self.knowledge.insert(*id, WordKnowledge::new(*id));
+            }
         }
     }
 
     pub fn estimate_serialized_size(&self) -> usize {
-        self.knowledge.len() * size_of::<WordKnowledge>() + size_of::<KnowledgeData>()
+        self.knowledge.len() * size_of::<WordKnowledge>()
+            + self.suspended.len() * size_of::<WordID>()
+            + self.lapses.len() * size_of::<(WordID, u32)>()
+            + (self.failures.len() + self.buried.len()) * size_of::<(WordID, i64)>()
+            + self
+                .attributes
+                .keys()
+                .map(|(_, name)| name.len() + size_of::<WordKnowledge>())
+                .sum::<usize>()
+            + self.cloze.len() * size_of::<WordKnowledge>()
+            + size_of::<KnowledgeData>()
     }
 
     pub fn save_to<T: Write>(&self, writable: &mut T) -> Result<usize, Error> {
         let size_estimate = self.estimate_serialized_size();
         let mut kw_data = vec![0u8; size_estimate];
-        let kw_size = postcard::to_slice(&self.knowledge, &mut kw_data)?.len();
+        let entries: Vec<&WordKnowledge> = self.knowledge.values().collect();
+        let kw_size = postcard::to_slice(&entries, &mut kw_data)?.len();
         kw_data.truncate(kw_size);
 
+        let now = Utc::now().timestamp();
         let data = KnowledgeData {
-            dict_title: self.dict.title.clone().into(),
+            dict_id: self.dict.uid,
             knowledge_data: kw_data.into_boxed_slice(),
             active_words: self.active_words,
+            suspended: self.suspended.iter().copied().collect(),
+            lapses: self.lapses.iter().map(|(id, n)| (*id, *n)).collect(),
+            failures: self.failures.iter().map(|(id, t)| (*id, *t)).collect(),
+            // burials that ran out are not worth keeping
+            buried: self
+                .buried
+                .iter()
+                .filter(|(_, t)| **t > now)
+                .map(|(id, t)| (*id, *t))
+                .collect(),
+            attributes: self
+                .attributes
+                .iter()
+                .map(|((_, name), k)| (name.to_owned(), k.clone()))
+                .collect(),
+            cloze: self.cloze.values().cloned().collect(),
         };
 
         let mut alloc = vec![0u8; size_estimate];
@@ -190,11 +357,15 @@ impl Knowledge {
     //     }
     // }
 
-    pub fn load_from<'a, T>(readable: &mut T, container: &DictMap) -> Result<Knowledge, Error>
+    /// `None` if the dictionary the knowledge is of isn't loaded.
+    pub fn load_from<'a, T>(
+        readable: &mut T,
+        container: &DictMap,
+    ) -> Result<Option<Knowledge>, Error>
     where
         T: Read,
     {
-        let mut file = filemanager::read_file(readable)?;
+        let file = filemanager::read_file(readable)?;
 
         if file.header != KNOW_HEADER {
             return Err("Invalid File Header!")?;
@@ -202,79 +373,185 @@ impl Knowledge {
 
         match file.version.as_str() {
             KNOW_VERSION => {
-                let data = &mut file.data[..];
-
-                let know_data: KnowledgeData = postcard::from_bytes(&data)?;
+                let data: KnowledgeData = postcard::from_bytes(&file.data)?;
 
-                let dict = container
-                    .get(&know_data.dict_title.to_string())
-                    .ok_or("Dict not found!")?
-                    .clone();
-                let knowledge = postcard::from_bytes(&know_data.knowledge_data)?;
+                let dict = match container.get(&data.dict_id) {
+                    Some(dict) => dict.clone(),
+                    None => return Ok(None),
+                };
+                let entries: Vec<WordKnowledge> = postcard::from_bytes(&data.knowledge_data)?;
+
+                let mut knowl = Knowledge::from_entries(dict, entries, data.active_words);
+                knowl.suspended = data.suspended.iter().copied().collect();
+                knowl.lapses = data.lapses.iter().copied().collect();
+                knowl.failures = data.failures.iter().copied().collect();
+                knowl.buried = data.buried.iter().copied().collect();
+                knowl.attributes = data
+                    .attributes
+                    .into_vec()
+                    .into_iter()
+                    .map(|(name, k)| ((k.word_id, name), k))
+                    .collect();
+                knowl.cloze = data
+                    .cloze
+                    .into_vec()
+                    .into_iter()
+                    .map(|k| (k.word_id, k))
+                    .collect();
+
+                Ok(Some(knowl))
+            }
+            "0.2" => {
+                let data: word_knowledge::v0_2::KnowledgeData = postcard::from_bytes(&file.data)?;
 
-                Ok(Knowledge {
+                let dict = match container.find_by_title(data.dict_title.as_str()) {
+                    Some(dict) => dict.clone(),
+                    None => return Ok(None),
+                };
+                let entries: Vec<WordKnowledge> = postcard::from_bytes(&data.knowledge_data)?;
+
+                // word ids used to be indices into the dictionary
+                let entries: Vec<WordKnowledge> = entries
+                    .into_iter()
+                    .filter_map(|mut k| {
+                        k.word_id = dict.get_id_from_index(k.word_id.as_index())?;
+                        Some(k)
+                    })
+                    .collect();
+
+                Ok(Some(Knowledge::from_entries(
                     dict,
-                    knowledge,
-                    active_words: know_data.active_words,
-                })
+                    entries,
+                    data.active_words,
+                )))
             }
             v => {
-                let knowl = match v {
-                    _ => {
-                        println!("{}", v);
-                        Err("Unknown File Version!")?
-                    }
-                };
-
-                Ok(knowl)
+                println!("{}", v);
+                Err("Unknown File Version!")?
             }
         }
     }
 
     pub fn practice(&mut self, word: WordID, correct: bool) {
-        let i: usize = word.into();
-        let info = &mut self.knowledge[i];
+        let info = self
+            .knowledge
+            .entry(word)
+            .or_insert_with(|| WordKnowledge::new(word));
 
-        let lp = info.last_practice;
-        info.last_practice = Some(Utc::now().into());
+        if info.record_answer(correct) {
+            *self.lapses.entry(word).or_insert(0) += 1;
+        }
+        if !correct {
+            self.failures.insert(word, Utc::now().timestamp());
+        }
+    }
 
-        match lp {
-            Some(time) => {
-                let time_delta = info.last_practice.unwrap() - time;
-                // > 1 if practiced after expected half-life, < 1 if practiced before
-                let time_factor = time_delta.num_minutes() as f32 / info.half_life;
+    /// Records an answer to the attribute `name` of `word`. Doesn't touch the knowledge of the
+    /// word itself, knowing a word doesn't mean knowing its forms.
+    pub fn practice_attribute(&mut self, word: WordID, name: &str, correct: bool) {
+        self.attributes
+            .entry((word, name.to_owned()))
+            .or_insert_with(|| WordKnowledge::new(word))
+            .record_answer(correct);
+    }
 
-                // A 0 time factor will result in no change, a 1 time factor will change by a factor of 2
-                let mut multiplier = 1.0 + 1.0 * time_factor;
+    /// `None` if the attribute was never drilled.
+    pub fn get_attribute_knowledge(&self, word: WordID, name: &str) -> Option<&WordKnowledge> {
+        self.attributes.get(&(word, name.to_owned()))
+    }
 
-                // If the answer was wrong, take reciprocal
-                if !correct {
-                    multiplier = multiplier.recip();
-                }
+    /// Records an answer to a cloze card of `word`. Like attributes, this is tracked apart from
+    /// the word itself.
+    pub fn practice_cloze(&mut self, word: WordID, correct: bool) {
+        self.cloze
+            .entry(word)
+            .or_insert_with(|| WordKnowledge::new(word))
+            .record_answer(correct);
+    }
 
-                info.half_life = f32::max(info.half_life * multiplier, MIN_HALF_LIFE);
-            }
-            None => {
-                if correct {
-                    // Set half-life to two days if user alerady knows word the first time seeing it.
-                    info.half_life = 60.0 * 24.0 * 2.0;
-                } else {
-                    info.half_life = MIN_HALF_LIFE;
-                }
-            }
-        }
+    /// `None` if the word never had a cloze card.
+    pub fn get_cloze_knowledge(&self, word: WordID) -> Option<&WordKnowledge> {
+        self.cloze.get(&word)
+    }
+
+    /// Knowledge of words that are no longer in the dictionary.
+    pub fn get_archived(&self) -> Box<[&WordKnowledge]> {
+        self.knowledge
+            .values()
+            .filter(|k| !self.dict.contains_id(k.word_id))
+            .collect()
+    }
+
+    /// Ids of the words that are no longer in the dictionary, so they aren't given out again.
+    pub fn get_archived_ids(&self) -> impl Iterator<Item = WordID> + '_ {
+        self.knowledge
+            .keys()
+            .copied()
+            .filter(|id| !self.dict.contains_id(*id))
     }
 
     pub fn get_word_knowledge(&self, word: WordID) -> &WordKnowledge {
-        let i: usize = word.into();
+        &self.knowledge[&word]
+    }
+
+    pub fn get_status(&self, word: WordID, now: DateTime<Utc>) -> Option<WordStatus> {
+        let k = self.knowledge.get(&word)?;
+        let lapses = self.get_lapses(word);
+
+        Some(WordStatus {
+            half_life: k.half_life,
+            last_practice: k.last_practice.map(|t| t.timestamp()),
+            recall: k.calculate_p_value(now),
+            due: k.calculate_due().map(|t| t.timestamp()),
+            lapses,
+            suspended: self.is_suspended(word),
+            leech: lapses >= LEECH_LAPSES,
+        })
+    }
+
+    pub fn get_lapses(&self, word: WordID) -> u32 {
+        self.lapses.get(&word).copied().unwrap_or(0)
+    }
+
+    /// Unix seconds of the last wrong answer to `word`.
+    pub fn get_last_failure(&self, word: WordID) -> Option<i64> {
+        self.failures.get(&word).copied()
+    }
+
+    /// Leaves `word` out of reviews until `until`, in unix seconds.
+    pub fn bury(&mut self, word: WordID, until: i64) {
+        let t = self.buried.entry(word).or_insert(until);
+        *t = (*t).max(until);
+    }
+
+    pub fn is_buried(&self, word: WordID, now: DateTime<Utc>) -> bool {
+        self.buried
+            .get(&word)
+            .map_or(false, |until| *until > now.timestamp())
+    }
+
+    pub fn is_suspended(&self, word: WordID) -> bool {
+        self.suspended.contains(&word)
+    }
 
-        &self.knowledge[i]
+    pub fn set_suspended(&mut self, word: WordID, suspended: bool) {
+        if suspended {
+            self.suspended.insert(word);
+        } else {
+            self.suspended.remove(&word);
+        }
     }
 
     pub fn get_dict(&self) -> Arc<Dictionary> {
         self.dict.clone()
     }
 
+    /// Points this knowledge at a rebuilt copy of its dictionary.
+    pub fn set_dict(&mut self, dict: Arc<Dictionary>) {
+        self.dict = dict;
+        self.fill_missing();
+    }
+
     pub fn get_active_words(&self) -> usize {
         self.active_words
     }
@@ -283,3 +560,128 @@ impl Knowledge {
         self.active_words = amount;
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::super::dictionary::tests::v1_0_file;
+    use super::*;
+
+    /// A `WordKnowledge` as 0.2 wrote it, keyed by the index of the word.
+    #[derive(Serialize)]
+    struct IndexedKnowledge {
+        index: usize,
+        last_practice: Option<i64>,
+        half_life: f32,
+    }
+
+    fn to_bytes<T: Serialize>(value: &T) -> Box<[u8]> {
+        let mut buf = vec![0u8; 4096];
+        let len = postcard::to_slice(value, &mut buf).unwrap().len();
+        buf.truncate(len);
+
+        buf.into_boxed_slice()
+    }
+
+    fn v0_2_file(title: &str, active_words: usize, entries: &[IndexedKnowledge]) -> Vec<u8> {
+        let data = word_knowledge::v0_2::KnowledgeData {
+            dict_title: title.to_owned().into(),
+            active_words,
+            knowledge_data: to_bytes(&entries),
+        };
+
+        let mut file = Vec::new();
+        filemanager::save_file(
+            &mut file,
+            KNOW_HEADER.to_owned(),
+            "0.2".to_owned(),
+            to_bytes(&data),
+        )
+        .unwrap();
+
+        file
+    }
+
+    fn load_dict(file: &[u8]) -> (Arc<Dictionary>, DictMap) {
+        let dict = Arc::new(Dictionary::load_from(&mut &file[..]).unwrap().0);
+        let mut container = DictMap::new();
+        container.insert(dict.clone());
+
+        (dict, container)
+    }
+
+    /// Half-life of the word with `definition`.
+    fn half_life(knowl: &Knowledge, definition: &str) -> f32 {
+        let dict = knowl.get_dict();
+        let id = dict
+            .get_word_ids()
+            .iter()
+            .copied()
+            .find(|id| dict.get_word_from_id(*id).definition == definition)
+            .unwrap();
+
+        knowl.get_word_knowledge(id).half_life
+    }
+
+    #[test]
+    fn moves_0_2_progress_to_word_ids() {
+        let dict_file = v1_0_file(
+            "Old",
+            &[("bank", "money"), ("river", "water"), ("bank", "shore")],
+        );
+        let (_, container) = load_dict(&dict_file);
+
+        let file = v0_2_file(
+            "Old",
+            2,
+            &[
+                IndexedKnowledge {
+                    index: 0,
+                    last_practice: Some(1000),
+                    half_life: 100.0,
+                },
+                IndexedKnowledge {
+                    index: 2,
+                    last_practice: Some(2000),
+                    half_life: 300.0,
+                },
+                // past the end of the dictionary
+                IndexedKnowledge {
+                    index: 7,
+                    last_practice: Some(3000),
+                    half_life: 700.0,
+                },
+            ],
+        );
+        let knowl = Knowledge::load_from(&mut &file[..], &container)
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(knowl.get_active_words(), 2);
+        assert_eq!(half_life(&knowl, "money"), 100.0);
+        assert_eq!(half_life(&knowl, "shore"), 300.0);
+        assert_eq!(half_life(&knowl, "water"), MIN_HALF_LIFE);
+        assert!(knowl.get_archived().is_empty());
+
+        // saved as 0.3, it finds its words again after a restart loads the 1.0 dictionary anew
+        let mut saved = Vec::new();
+        knowl.save_to(&mut saved).unwrap();
+        let (_, container) = load_dict(&dict_file);
+        let knowl = Knowledge::load_from(&mut &saved[..], &container)
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(half_life(&knowl, "money"), 100.0);
+        assert_eq!(half_life(&knowl, "shore"), 300.0);
+        assert_eq!(half_life(&knowl, "water"), MIN_HALF_LIFE);
+    }
+
+    #[test]
+    fn skips_0_2_of_missing_dictionary() {
+        let (_, container) = load_dict(&v1_0_file("Old", &[("bank", "money")]));
+        let file = v0_2_file("Gone", 1, &[]);
+
+        assert!(Knowledge::load_from(&mut &file[..], &container)
+            .unwrap()
+            .is_none());
+    }
+}
@@ -0,0 +1,112 @@
+use serde::Serialize;
+
+use crate::error::Error;
+
+use super::{Dictionary, WordID};
+
+/// Outcome of importing a lemma table.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct LemmaReport {
+    /// Forms that were mapped to a headword
+    pub added: usize,
+    /// Lines whose headword is not in the dictionary
+    pub skipped: usize,
+}
+
+impl Dictionary {
+    /// Headwords `form` is an inflection of, e.g. "run" for "ran".
+    pub fn find_lemma(&self, form: &str) -> &[WordID] {
+        match self.lemmas.get(&self.normalize(form)) {
+            Some(ids) => ids,
+            None => &[],
+        }
+    }
+
+    /// Whether a typed answer names the word `id`, by its headword or by an inflected form.
+    pub fn accepts_answer(&self, id: WordID, answer: &str) -> bool {
+        let headword = &self.get_word_from_id(id).text;
+
+        self.normalize(answer) == self.normalize(headword) || self.find_lemma(answer).contains(&id)
+    }
+
+    /// Builds a copy of this dictionary with the forms of a lemma table added, or replacing the
+    /// old ones.
+    ///
+    /// Every line of the table holds an inflected form and its headword, separated by a tab.
+    /// Empty lines and lines starting with `#` are ignored, as are headwords this dictionary
+    /// doesn't have. A form of a headword with homographs belongs to all of them.
+    pub fn with_lemma_table(
+        &self,
+        table: &str,
+        replace: bool,
+    ) -> Result<(Dictionary, LemmaReport), Error> {
+        let mut entries = if replace {
+            Vec::new()
+        } else {
+            self.lemma_entries()
+        };
+        let mut report = LemmaReport::default();
+
+        for (n, line) in table.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let mut columns = line.split('\t').map(|c| c.trim());
+            let (form, headword) = match (columns.next(), columns.next()) {
+                (Some(form), Some(headword)) if !form.is_empty() && !headword.is_empty() => {
+                    (form, headword)
+                }
+                _ => {
+                    return Err(format!(
+                        "Line {}: expected a form and a headword separated by a tab",
+                        n + 1
+                    ))?
+                }
+            };
+
+            let ids = self.find_homographs(headword);
+            if ids.is_empty() {
+                report.skipped += 1;
+                continue;
+            }
+
+            for id in ids {
+                entries.push((self.normalize(form), *id));
+            }
+            report.added += 1;
+        }
+
+        let mut dict = self.rebuilt(
+            self.words.to_vec(),
+            self.ids.to_vec(),
+            self.title.to_owned(),
+        );
+        dict.lemmas.clear();
+        dict.insert_lemmas(entries);
+
+        Ok((dict, report))
+    }
+
+    pub(super) fn lemma_entries(&self) -> Vec<(String, WordID)> {
+        self.lemmas
+            .iter()
+            .flat_map(|(form, ids)| ids.iter().map(move |id| (form.to_owned(), *id)))
+            .collect()
+    }
+
+    /// Adds lemma entries, leaving out the ones of words this dictionary doesn't have.
+    pub(super) fn insert_lemmas(&mut self, entries: impl IntoIterator<Item = (String, WordID)>) {
+        for (form, id) in entries {
+            if !self.contains_id(id) {
+                continue;
+            }
+
+            let ids = self.lemmas.entry(form).or_default();
+            if !ids.contains(&id) {
+                ids.push(id);
+            }
+        }
+    }
+}
@@ -0,0 +1,53 @@
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+
+/// Descriptive information about a dictionary, used to tell look-alike dictionaries apart.
+///
+/// Languages are ISO 639 codes. The source language is the language of the headwords, the target
+/// language the one of the definitions. Timestamps are unix seconds.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DictMetadata {
+    pub source_language: Option<String>,
+    pub target_language: Option<String>,
+    pub author: Option<String>,
+    pub version: Option<String>,
+    pub description: Option<String>,
+    pub license: Option<String>,
+    pub created: Option<i64>,
+    pub modified: Option<i64>,
+}
+
+impl DictMetadata {
+    pub fn size_of(&self) -> usize {
+        let strings = [
+            &self.source_language,
+            &self.target_language,
+            &self.author,
+            &self.version,
+            &self.description,
+            &self.license,
+        ];
+
+        strings
+            .iter()
+            .flat_map(|s| s.as_ref())
+            .map(|s| s.len())
+            .sum::<usize>()
+            + std::mem::size_of::<Self>()
+    }
+
+    pub(super) fn touch(&mut self) {
+        self.modified = Some(Utc::now().timestamp());
+    }
+}
+
+/// Checks for a semantic version like `1.2.3`, `1.0.0-beta` or `2.1.0+build.5`.
+pub fn is_semver(version: &str) -> bool {
+    let core = version.split(|c| c == '-' || c == '+').next().unwrap_or("");
+    let parts: Vec<&str> = core.split('.').collect();
+
+    parts.len() == 3
+        && parts
+            .iter()
+            .all(|p| !p.is_empty() && p.chars().all(|c| c.is_ascii_digit()))
+}
@@ -1,9 +1,21 @@
+mod cloze;
 mod dictionary;
+mod filter;
 mod knowledge;
+mod lemma;
+mod metadata;
+mod preview;
+mod search;
 mod word;
 
+pub use cloze::*;
 pub use dictionary::*;
+pub use filter::*;
 pub use knowledge::*;
+pub use lemma::*;
+pub use metadata::*;
+pub use preview::*;
+pub use search::*;
 pub use word::*;
 
 const MAX_AWARD: u32 = 50;
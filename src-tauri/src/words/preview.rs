@@ -0,0 +1,119 @@
+use std::collections::HashMap;
+
+use serde::Serialize;
+
+use crate::tools::dict_map::DictMap;
+
+use super::{DictMetadata, Dictionary};
+
+const HISTOGRAM_BUCKETS: u64 = 10;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct EmptyField {
+    pub word_index: usize,
+    pub text: String,
+    pub field: &'static str,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct HistogramBucket {
+    pub min: u32,
+    pub max: u32,
+    pub count: usize,
+}
+
+/// Summary of a parsed dictionary, shown to the user before anything is written to the dict dir.
+#[derive(Debug, Clone, Serialize)]
+pub struct DictPreview {
+    pub title: String,
+    pub metadata: DictMetadata,
+    pub word_count: usize,
+    pub duplicates: Box<[String]>,
+    pub empty_fields: Box<[EmptyField]>,
+    pub obscurity_histogram: Box<[HistogramBucket]>,
+    pub title_collision: bool,
+    /// The dict dir already has a file of the name the import would write
+    pub file_collision: bool,
+}
+
+impl DictPreview {
+    /// `empty_fields` are the ones the parser found, e.g. empty optional xml elements.
+    pub fn create(
+        dict: &Dictionary,
+        installed: &DictMap,
+        mut empty_fields: Vec<EmptyField>,
+        file_collision: bool,
+    ) -> DictPreview {
+        let mut counts: HashMap<&str, usize> = HashMap::new();
+        let mut duplicates = Vec::new();
+
+        for (i, word) in dict.words.iter().enumerate() {
+            let count = counts.entry(word.text.as_str()).or_insert(0);
+            *count += 1;
+            if *count == 2 {
+                duplicates.push(word.text.to_owned());
+            }
+
+            let mut empty = |field| {
+                empty_fields.push(EmptyField {
+                    word_index: i,
+                    text: word.text.to_owned(),
+                    field,
+                })
+            };
+
+            if word.text.trim().is_empty() {
+                empty("text");
+            }
+            if word.definition.trim().is_empty() {
+                empty("definition");
+            }
+            if let Some(pronunciation) = &word.pronunciation {
+                if pronunciation.trim().is_empty() {
+                    empty("pronunciation");
+                }
+            }
+        }
+
+        DictPreview {
+            title: dict.title.to_owned(),
+            metadata: dict.metadata.clone(),
+            word_count: dict.words.len(),
+            duplicates: duplicates.into_boxed_slice(),
+            empty_fields: empty_fields.into_boxed_slice(),
+            obscurity_histogram: obscurity_histogram(dict),
+            title_collision: installed.find_by_title(&dict.title).is_some(),
+            file_collision,
+        }
+    }
+}
+
+fn obscurity_histogram(dict: &Dictionary) -> Box<[HistogramBucket]> {
+    let (min, max) = match (
+        dict.obscurity_index.keys().next(),
+        dict.obscurity_index.keys().next_back(),
+    ) {
+        (Some(min), Some(max)) => (*min, *max),
+        _ => return Box::new([]),
+    };
+
+    // u64 so the bucket bounds can't overflow when a long exponential dictionary reaches u32::MAX
+    let width = ((max - min) as u64 / HISTOGRAM_BUCKETS) + 1;
+
+    let mut buckets: Vec<HistogramBucket> = (0..HISTOGRAM_BUCKETS)
+        .map(|b| (min as u64 + b * width, min as u64 + (b + 1) * width - 1))
+        .take_while(|(lo, _)| *lo <= max as u64)
+        .map(|(lo, hi)| HistogramBucket {
+            min: lo as u32,
+            max: hi.min(max as u64) as u32,
+            count: 0,
+        })
+        .collect();
+
+    for (obs, ids) in &dict.obscurity_index {
+        let bucket = ((obs - min) as u64 / width) as usize;
+        buckets[bucket].count += ids.lock().unwrap().len();
+    }
+
+    buckets.into_boxed_slice()
+}
@@ -0,0 +1,179 @@
+use serde::{Deserialize, Serialize};
+
+use crate::tools::normalize::{fold, normalize};
+
+use super::{Dictionary, WordID};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum SearchMode {
+    /// Only matches the start of a field or of a word inside it
+    Prefix,
+    #[default]
+    Substring,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct SearchOptions {
+    pub mode: SearchMode,
+    pub accent_sensitive: bool,
+    pub limit: usize,
+}
+
+impl Default for SearchOptions {
+    fn default() -> Self {
+        SearchOptions {
+            mode: SearchMode::Substring,
+            accent_sensitive: false,
+            limit: 50,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum SearchField {
+    Text,
+    Pronunciation,
+    Definition,
+}
+
+impl SearchField {
+    fn weight(self) -> u32 {
+        match self {
+            SearchField::Text => 3,
+            SearchField::Pronunciation => 2,
+            SearchField::Definition => 2,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum MatchKind {
+    /// The whole field is the query
+    Exact,
+    /// The field starts with the query
+    Prefix,
+    /// A word inside the field starts with the query
+    WordPrefix,
+    Substring,
+}
+
+impl MatchKind {
+    fn weight(self) -> u32 {
+        match self {
+            MatchKind::Exact => 8,
+            MatchKind::Prefix => 4,
+            MatchKind::WordPrefix => 3,
+            MatchKind::Substring => 1,
+        }
+    }
+
+    /// Finds how `query` occurs in `field`. Both are expected to be normalized the same way.
+    fn find(field: &str, query: &str) -> Option<MatchKind> {
+        if field == query {
+            return Some(MatchKind::Exact);
+        }
+        if field.starts_with(query) {
+            return Some(MatchKind::Prefix);
+        }
+
+        let mut found = None;
+        for (i, _) in field.match_indices(query) {
+            let word_start = !field[..i]
+                .chars()
+                .next_back()
+                .map_or(false, |c| c.is_alphanumeric());
+
+            if word_start {
+                return Some(MatchKind::WordPrefix);
+            }
+            found = Some(MatchKind::Substring);
+        }
+
+        found
+    }
+}
+
+/// The best match of a search in one word.
+#[derive(Debug, Clone)]
+pub struct WordMatch {
+    pub id: WordID,
+    pub field: SearchField,
+    pub kind: MatchKind,
+    pub score: u32,
+}
+
+fn prepare(text: &str, language: Option<&str>, options: &SearchOptions) -> String {
+    if options.accent_sensitive {
+        normalize(text, language)
+    } else {
+        fold(text, language)
+    }
+}
+
+impl Dictionary {
+    /// Finds every word whose headword, pronunciation or definition contains `query`.
+    ///
+    /// Headwords and pronunciations are compared with the rules of the source language,
+    /// definitions with the ones of the target language.
+    pub fn search(&self, query: &str, options: &SearchOptions) -> Vec<WordMatch> {
+        let source = self.metadata.source_language.as_deref();
+        let target = self.metadata.target_language.as_deref();
+
+        let source_query = prepare(query, source, options);
+        let target_query = prepare(query, target, options);
+        if source_query.is_empty() {
+            return Vec::new();
+        }
+
+        let mut out = Vec::new();
+
+        for (word, id) in self.words.iter().zip(self.ids.iter()) {
+            let mut fields = vec![
+                (SearchField::Text, Some(&word.text), source, &source_query),
+                (
+                    SearchField::Pronunciation,
+                    word.pronunciation.as_ref(),
+                    source,
+                    &source_query,
+                ),
+                (
+                    SearchField::Definition,
+                    Some(&word.definition),
+                    target,
+                    &target_query,
+                ),
+            ];
+            fields.extend(
+                word.senses
+                    .iter()
+                    .map(|s| (SearchField::Definition, Some(s), target, &target_query)),
+            );
+
+            let best = fields
+                .iter()
+                .filter_map(|(field, text, language, query)| {
+                    let text = prepare(text.as_ref()?, *language, options);
+                    let kind = MatchKind::find(&text, query)?;
+
+                    if options.mode == SearchMode::Prefix && kind == MatchKind::Substring {
+                        return None;
+                    }
+
+                    Some((*field, kind))
+                })
+                .max_by_key(|(field, kind)| field.weight() * kind.weight());
+
+            if let Some((field, kind)) = best {
+                out.push(WordMatch {
+                    id: *id,
+                    field,
+                    kind,
+                    score: field.weight() * kind.weight(),
+                });
+            }
+        }
+
+        out
+    }
+}
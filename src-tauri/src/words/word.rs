@@ -1,5 +1,7 @@
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
+use struct_version_manager::version_macro::version_mod;
+
 use crate::tools::crypt_string::PermutedString;
 
 fn serialize_as_ecrypted<S: Serializer>(string: &String, s: S) -> Result<S::Ok, S::Error> {
@@ -33,24 +35,158 @@ fn deserialize_as_ecrypted_op<'de, D: Deserializer<'de>>(s: D) -> Result<Option<
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct Word {
-    #[serde(
-        serialize_with = "serialize_as_ecrypted",
-        deserialize_with = "deserialize_as_ecrypted"
-    )]
-    pub text: String,
+fn serialize_as_ecrypted_vec<S: Serializer>(strings: &[String], s: S) -> Result<S::Ok, S::Error> {
+    let enc: Vec<PermutedString> = strings.iter().map(|s| s.to_owned().into()).collect();
+    enc.serialize(s)
+}
+
+fn deserialize_as_ecrypted_vec<'de, D: Deserializer<'de>>(s: D) -> Result<Vec<String>, D::Error> {
+    let enc = Vec::<PermutedString>::deserialize(s)?;
+
+    Ok(enc.into_iter().map(|es| es.to_string()).collect())
+}
+
+/// Grammatical property or inflected form of a word, e.g. "gender" or "past 1st person plural".
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WordAttribute {
+    pub name: String,
     #[serde(
         serialize_with = "serialize_as_ecrypted",
         deserialize_with = "deserialize_as_ecrypted"
     )]
-    pub definition: String,
-    #[serde(
-        serialize_with = "serialize_as_ecrypted_op",
-        deserialize_with = "deserialize_as_ecrypted_op"
-    )]
-    pub pronunciation: Option<String>,
-    pub obscurity: u32,
+    pub value: String,
+}
+
+#[version_mod(Word)]
+mod word_data {
+    pub mod v1_0 {
+        use serde::{Deserialize, Serialize};
+        use struct_version_manager::version_macro::version;
+
+        use super::super::{deserialize_as_ecrypted, serialize_as_ecrypted};
+        use super::super::{deserialize_as_ecrypted_op, serialize_as_ecrypted_op};
+
+        #[derive(Debug, Clone, Serialize, Deserialize)]
+        #[version("1.0")]
+        pub struct Word {
+            #[serde(
+                serialize_with = "serialize_as_ecrypted",
+                deserialize_with = "deserialize_as_ecrypted"
+            )]
+            pub text: String,
+            #[serde(
+                serialize_with = "serialize_as_ecrypted",
+                deserialize_with = "deserialize_as_ecrypted"
+            )]
+            pub definition: String,
+            #[serde(
+                serialize_with = "serialize_as_ecrypted_op",
+                deserialize_with = "deserialize_as_ecrypted_op"
+            )]
+            pub pronunciation: Option<String>,
+            pub obscurity: u32,
+        }
+    }
+
+    pub mod v1_1 {
+        use serde::{Deserialize, Serialize};
+        use struct_version_manager::version_macro::version;
+
+        use super::super::WordAttribute;
+        use super::super::{deserialize_as_ecrypted, serialize_as_ecrypted};
+        use super::super::{deserialize_as_ecrypted_op, serialize_as_ecrypted_op};
+        use super::super::{deserialize_as_ecrypted_vec, serialize_as_ecrypted_vec};
+
+        /// Everything that gives away a word or its meaning is stored encrypted. Labels like the
+        /// part of speech, tags, the concept and file names are not.
+        #[derive(Debug, Clone, Serialize, Deserialize)]
+        #[version("1.1")]
+        pub struct Word {
+            #[serde(
+                serialize_with = "serialize_as_ecrypted",
+                deserialize_with = "deserialize_as_ecrypted"
+            )]
+            pub text: String,
+            #[serde(
+                serialize_with = "serialize_as_ecrypted",
+                deserialize_with = "deserialize_as_ecrypted"
+            )]
+            pub definition: String,
+            #[serde(
+                serialize_with = "serialize_as_ecrypted_op",
+                deserialize_with = "deserialize_as_ecrypted_op"
+            )]
+            pub pronunciation: Option<String>,
+            pub obscurity: u32,
+            /// File name of an audio clip in the dictionary's media folder
+            pub audio: Option<String>,
+            /// File name of an image in the dictionary's media folder
+            pub image: Option<String>,
+            /// Free-form, e.g. "noun" or "verb"
+            pub part_of_speech: Option<String>,
+            /// Numbered senses of the word, the definition sums them up
+            #[serde(
+                serialize_with = "serialize_as_ecrypted_vec",
+                deserialize_with = "deserialize_as_ecrypted_vec"
+            )]
+            pub senses: Vec<String>,
+            /// Example sentences using the word
+            #[serde(
+                serialize_with = "serialize_as_ecrypted_vec",
+                deserialize_with = "deserialize_as_ecrypted_vec"
+            )]
+            pub examples: Vec<String>,
+            #[serde(
+                serialize_with = "serialize_as_ecrypted_vec",
+                deserialize_with = "deserialize_as_ecrypted_vec"
+            )]
+            pub synonyms: Vec<String>,
+            #[serde(
+                serialize_with = "serialize_as_ecrypted_vec",
+                deserialize_with = "deserialize_as_ecrypted_vec"
+            )]
+            pub antonyms: Vec<String>,
+            pub tags: Vec<String>,
+            /// Shared with the entries of other dictionaries that mean the same, e.g. the Spanish
+            /// and the French word for "dog"
+            pub concept: Option<String>,
+            /// Drilled separately from the word itself
+            pub attributes: Vec<WordAttribute>,
+        }
+
+        impl From<super::v1_0::Word> for Word {
+            fn from(value: super::v1_0::Word) -> Self {
+                Word {
+                    text: value.text,
+                    definition: value.definition,
+                    pronunciation: value.pronunciation,
+                    obscurity: value.obscurity,
+                    audio: None,
+                    image: None,
+                    part_of_speech: None,
+                    senses: Vec::new(),
+                    examples: Vec::new(),
+                    synonyms: Vec::new(),
+                    antonyms: Vec::new(),
+                    tags: Vec::new(),
+                    concept: None,
+                    attributes: Vec::new(),
+                }
+            }
+        }
+    }
+}
+
+pub(super) use word_data::v1_0::Word as WordV1_0;
+pub use word_data::v1_1::Word;
+
+impl Word {
+    pub fn get_media(&self) -> impl Iterator<Item = &str> {
+        self.audio
+            .iter()
+            .chain(self.image.iter())
+            .map(|m| m.as_str())
+    }
 }
 
 pub mod for_frontend {
@@ -61,6 +197,26 @@ pub mod for_frontend {
         pub definition: String,
         pub pronunciation: Option<String>,
         pub obscurity: u32,
+        #[serde(default)]
+        pub audio: Option<String>,
+        #[serde(default)]
+        pub image: Option<String>,
+        #[serde(default)]
+        pub part_of_speech: Option<String>,
+        #[serde(default)]
+        pub senses: Vec<String>,
+        #[serde(default)]
+        pub examples: Vec<String>,
+        #[serde(default)]
+        pub synonyms: Vec<String>,
+        #[serde(default)]
+        pub antonyms: Vec<String>,
+        #[serde(default)]
+        pub tags: Vec<String>,
+        #[serde(default)]
+        pub concept: Option<String>,
+        #[serde(default)]
+        pub attributes: Vec<super::WordAttribute>,
     }
 
     impl From<super::Word> for Word {
@@ -70,6 +226,37 @@ pub mod for_frontend {
                 definition: value.definition,
                 pronunciation: value.pronunciation,
                 obscurity: value.obscurity,
+                audio: value.audio,
+                image: value.image,
+                part_of_speech: value.part_of_speech,
+                senses: value.senses,
+                examples: value.examples,
+                synonyms: value.synonyms,
+                antonyms: value.antonyms,
+                tags: value.tags,
+                concept: value.concept,
+                attributes: value.attributes,
+            }
+        }
+    }
+
+    impl From<Word> for super::Word {
+        fn from(value: Word) -> Self {
+            super::Word {
+                text: value.text,
+                definition: value.definition,
+                pronunciation: value.pronunciation,
+                obscurity: value.obscurity,
+                audio: value.audio,
+                image: value.image,
+                part_of_speech: value.part_of_speech,
+                senses: value.senses,
+                examples: value.examples,
+                synonyms: value.synonyms,
+                antonyms: value.antonyms,
+                tags: value.tags,
+                concept: value.concept,
+                attributes: value.attributes,
             }
         }
     }
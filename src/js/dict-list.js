@@ -19,6 +19,20 @@ async function pick_dict(dict) {
     }
 }
 
+function format_metadata(dict) {
+    let meta = dict.metadata;
+    let lines = [dict.word_count + " words"];
+    if (meta.source_language || meta.target_language) {
+        lines.push((meta.source_language ?? "?") + " → " + (meta.target_language ?? "?"));
+    }
+    if (meta.version) lines.push("Version " + meta.version);
+    if (meta.author) lines.push("By " + meta.author);
+    if (meta.license) lines.push("License: " + meta.license);
+    if (meta.description) lines.push(meta.description);
+
+    return lines.join("\n");
+}
+
 let plus = document.getElementById("add-dict");
 console.log(plus.clientHeight);
 function make_dict_button(dict, count=0) {
@@ -26,6 +40,7 @@ function make_dict_button(dict, count=0) {
     let div = document.createElement("div");
     button.appendChild(div);
     div.innerText = dict.name;
+    button.title = format_metadata(dict);
     div.classList.add("button");
     button.onclick = async (e) => {
         await pick_dict(dict);
@@ -44,6 +59,37 @@ function make_dict_button(dict, count=0) {
     fit(div, 4);
 }
 
+function format_parse_error(e) {
+    let location = "line " + e.line + ", column " + e.column;
+    if (e.word_index != null) {
+        location += " (word " + e.word_index + ")";
+    }
+    if (e.tag != null) {
+        location += " <" + e.tag + ">";
+    }
+
+    return location + ": " + e.message;
+}
+
+function format_preview(preview) {
+    let lines = [
+        "Import \"" + preview.title + "\"?",
+        preview.word_count + " words",
+    ];
+
+    if (preview.title_collision) {
+        lines.push("An installed dictionary already has this title.");
+    }
+    if (preview.duplicates.length > 0) {
+        lines.push("Duplicate words: " + preview.duplicates.join(", "));
+    }
+    if (preview.empty_fields.length > 0) {
+        lines.push(preview.empty_fields.length + " empty fields");
+    }
+
+    return lines.join("\n");
+}
+
 async function load_dict() {
     let files = await open({
         multiple: true,
@@ -53,9 +99,41 @@ async function load_dict() {
         }]
       });
 
-    files.forEach(async (f) => {
-        await invoke("import_dict", {filename: f});
-    })
+    let errors = [];
+    for (const f of files) {
+        let preview = await invoke("preview_dict", {filename: f})
+        .catch((error) => {
+            if (Array.isArray(error)) {
+                error.forEach((e) => errors.push(f + ": " + format_parse_error(e)));
+            } else {
+                errors.push(f + ": " + error);
+            }
+        });
+
+        if (preview == null) {
+            continue;
+        }
+        if (preview.file_collision) {
+            errors.push(f + ": A dictionary with this file name is already installed, update it instead!");
+            continue;
+        }
+        if (!confirm(format_preview(preview))) {
+            continue;
+        }
+
+        await invoke("import_dict", {filename: f})
+        .catch((error) => {
+            if (Array.isArray(error)) {
+                error.forEach((e) => errors.push(f + ": " + format_parse_error(e)));
+            } else {
+                errors.push(f + ": " + error);
+            }
+        });
+    }
+
+    if (errors.length > 0) {
+        alert(errors.join("\n"));
+    }
 
     await invoke("reload_files");
     window.location.reload();
@@ -11,6 +11,14 @@ const chk = document.getElementById("check");
 const word = document.getElementById("word");
 const pron = document.getElementById("pronunciation");
 const def = document.getElementById("definition");
+const image = document.getElementById("image");
+const audio = document.getElementById("audio");
+const pos = document.getElementById("part-of-speech");
+const examples = document.getElementById("examples");
+const related = document.getElementById("related");
+const tags = document.getElementById("tags");
+const note = document.getElementById("note");
+const star_button = document.getElementById("star");
 
 word.style.display = "";
 
@@ -48,8 +56,70 @@ function check() {
     if (word_obj.pronunciation != null) {
         pron.style.display = "";
     }
+
+    if (word_obj.image != null) {
+        image.style.display = "";
+    }
+
+    for (const e of [examples, related, tags, note]) {
+        if (e.textContent != "") {
+            e.style.display = "";
+        }
+    }
+
+    if (word_obj.audio != null) {
+        audio.style.display = "";
+        audio.play();
+    }
+}
+
+// dictionary data is only ever set as text, never parsed as HTML
+function set_lines(e, lines) {
+    e.replaceChildren();
+
+    lines.forEach((line, i) => {
+        if (i > 0) {
+            e.appendChild(document.createElement("br"));
+        }
+        e.appendChild(document.createTextNode(line));
+    });
+}
+
+function set_list(e, heading, items, tag) {
+    let list = document.createElement(tag);
+
+    for (const item of items) {
+        let li = document.createElement("li");
+        li.textContent = item;
+        list.appendChild(li);
+    }
+
+    e.replaceChildren(document.createTextNode(heading), list);
+}
+
+// Windows and Android serve custom protocols from http://<scheme>.localhost instead
+function media_url(dict, file) {
+    let path = dict.uid.id + "/" + encodeURIComponent(file);
+
+    if (/Windows|Android/.test(navigator.userAgent)) {
+        return "http://media.localhost/" + path;
+    }
+
+    return "media://localhost/" + path;
+}
+
+async function star() {
+    word_obj.starred = !word_obj.starred;
+    await invoke("star_current_word", {starred: word_obj.starred});
+
+    show_star();
 }
 
+function show_star() {
+    star_button.innerText = word_obj.starred ? "\u2605" : "\u2606";
+}
+
+window.star = star;
 window.yes = yes;
 window.no = no;
 window.check = check;
@@ -58,8 +128,69 @@ async function main() {
     word_obj = await invoke("get_current_word");
 
     word.innerText = word_obj.text;
-    pron.innerHTML = "Pronunciation:<br>" + word_obj.pronunciation;
-    def.innerHTML = "Definition:<br>" + word_obj.definition;
+    show_star();
+    set_lines(pron, ["Pronunciation:", word_obj.pronunciation]);
+
+    if (word_obj.part_of_speech != null) {
+        pos.innerText = word_obj.part_of_speech;
+        pos.style.display = "";
+    }
+
+    // pair sessions ask for the word given its linked words of another dictionary
+    let prompt = await invoke("get_current_prompt");
+    // drills ask for an attribute of the word instead of the word
+    let card = await invoke("get_current_card");
+
+    if (card != null && card.Attribute != null) {
+        let attribute = word_obj.attributes.find((a) => a.name == card.Attribute);
+
+        pos.innerText = card.Attribute + "?";
+        pos.style.display = "";
+        set_lines(def, [card.Attribute + ":", attribute.value]);
+    } else if (card != null && card.Cloze != null) {
+        let cloze = await invoke("get_current_cloze");
+
+        word.innerText = cloze.sentence;
+        set_lines(def, ["Answer:", cloze.answer + " (" + word_obj.text + ")"]);
+    } else if (prompt != null && prompt.length > 0) {
+        word.innerText = prompt.map((p) => p.word.text).join(", ");
+        set_lines(def, ["Answer:", word_obj.text]);
+    } else if (word_obj.senses.length > 1) {
+        set_list(def, "Definition:", word_obj.senses, "ol");
+    } else {
+        set_lines(def, ["Definition:", word_obj.definition]);
+    }
+
+    if (word_obj.examples.length > 0) {
+        set_list(examples, "Examples:", word_obj.examples, "ul");
+    }
+
+    let lines = [];
+    if (word_obj.synonyms.length > 0) {
+        lines.push("Synonyms: " + word_obj.synonyms.join(", "));
+    }
+    if (word_obj.antonyms.length > 0) {
+        lines.push("Antonyms: " + word_obj.antonyms.join(", "));
+    }
+    set_lines(related, lines);
+
+    if (word_obj.tags.length > 0) {
+        tags.textContent = "Tags: " + word_obj.tags.join(", ");
+    }
+
+    if (word_obj.note != null) {
+        note.innerText = "Note: " + word_obj.note;
+    }
+
+    let dict = await invoke("get_current_dict");
+
+    if (word_obj.image != null) {
+        image.src = media_url(dict, word_obj.image);
+    }
+
+    if (word_obj.audio != null) {
+        audio.src = media_url(dict, word_obj.audio);
+    }
 }
 
 main();
\ No newline at end of file